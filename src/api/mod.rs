@@ -1,16 +1,118 @@
 use exonum::{
-    crypto::Hash,
-    blockchain::Transaction,
+    crypto::{Hash, PublicKey},
+    blockchain::{Blockchain, Schema as CoreSchema, Transaction},
+    helpers::Height,
     node::TransactionSend,
-    api::{ServiceApiState, Result as ApiResult, ServiceApiBuilder},
+    api::{
+        self,
+        backends::actix::{FutureResponse, HttpRequest, RawHandler, RequestHandler},
+        ServiceApiState, Result as ApiResult, ServiceApiBuilder,
+    },
 };
-use blockchain::transactions::ExonumRngTransactions;
+use actix_web::{http::Method, HttpResponse};
+use futures::future;
+
+use std::sync::Arc;
+
+use blockchain::{
+    proof::RoundProof,
+    schema::{
+        Equivocation, RandomnessRequest, RngSchema, RoundInfo, ValidatorStats, ROUNDS_TABLE_INDEX,
+        ROUND_TABLE_INDEX,
+    },
+    transactions::ExonumRngTransactions,
+};
+use metrics;
+use SERVICE_ID;
+
+/// Content type of the Prometheus text exposition format.
+pub const METRICS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// Maximal number of rounds that can be requested at once.
+pub const MAX_ROUNDS_PER_REQUEST: u64 = 100;
 
 #[derive(Debug, Serialize)]
 struct TxResult {
     tx_hash: Hash
 }
 
+/// Query for a single finalized round.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct RoundQuery {
+    /// Number of the round.
+    pub round: u64,
+}
+
+/// Query for a range of finalized rounds.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct RoundsRangeQuery {
+    /// Number of the first round in the range.
+    pub from: u64,
+
+    /// Number of rounds to return, capped by `MAX_ROUNDS_PER_REQUEST`.
+    pub count: u64,
+}
+
+/// Query for a randomness request.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct RequestQuery {
+    /// Hash of the `TxRequestRandomness` transaction.
+    pub id: Hash,
+}
+
+/// Query for evidence of conflicting submissions.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct EquivocationsQuery {
+    /// Service key of the validator, evidence of all validators is returned if it's not set.
+    pub pub_key: Option<PublicKey>,
+}
+
+/// Query for participation statistics of validators.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ValidatorStatsQuery {
+    /// Service key of the validator, statistics of all validators are returned if it's not set.
+    pub pub_key: Option<PublicKey>,
+}
+
+/// Participation statistics of a validator.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValidatorParticipation {
+    /// Service key of the validator.
+    pub pub_key: PublicKey,
+
+    /// Statistics recorded on-chain.
+    pub stats: ValidatorStats,
+
+    /// Average number of blocks the validator takes to publish a commitment or a VDF result,
+    /// see `ValidatorStats::average_latency`.
+    pub average_latency: Option<f64>,
+}
+
+/// Status of the round that is in progress.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoundStatus {
+    /// Number of the round.
+    pub round: u64,
+
+    /// Combined seed, if validators have agreed on it.
+    pub seed: Option<Hash>,
+
+    /// VDF difficulty of the round.
+    pub difficulty: u64,
+
+    /// Validators that have published their seed commitments.
+    pub seed_commitments: Vec<PublicKey>,
+
+    /// Validators that have published valid VDF results.
+    pub vdf_results: Vec<PublicKey>,
+
+    /// Number of seed commitments or VDF results required to proceed.
+    pub threshold: usize,
+
+    /// Height starting from which the round is aborted if the current phase isn't completed.
+    pub deadline: Height,
+}
+
 fn post_transaction(state: &ServiceApiState, tx: ExonumRngTransactions) -> ApiResult<TxResult> {
     let transaction: Box<dyn Transaction> = tx.into();
     let tx_hash = transaction.hash();
@@ -18,6 +120,119 @@ fn post_transaction(state: &ServiceApiState, tx: ExonumRngTransactions) -> ApiRe
     Ok(TxResult { tx_hash })
 }
 
+fn latest_randomness(state: &ServiceApiState, _query: ()) -> ApiResult<RoundInfo> {
+    let schema = RngSchema::new(state.snapshot());
+    let rounds = schema.rounds();
+    (0..rounds.len())
+        .rev()
+        .filter_map(|round| rounds.get(round))
+        .find(|info| !info.failed())
+        .ok_or_else(|| api::Error::NotFound("No rounds have been finalized yet".to_owned()))
+}
+
+fn current_round(state: &ServiceApiState, _query: ()) -> ApiResult<RoundStatus> {
+    let schema = RngSchema::new(state.snapshot());
+    let seed_commitments = schema.validators_seed_commitments().keys().collect();
+    let vdf_results = schema.validators_vdf_results().keys().collect();
+
+    Ok(RoundStatus {
+        round: schema.current_round(),
+        seed: schema.last_seed().get(),
+        difficulty: schema.vdf_difficulty(),
+        seed_commitments,
+        vdf_results,
+        threshold: schema.quorum(),
+        deadline: schema.phase_deadline(),
+    })
+}
+
+fn round(state: &ServiceApiState, query: RoundQuery) -> ApiResult<RoundInfo> {
+    let schema = RngSchema::new(state.snapshot());
+    schema
+        .round_info(query.round)
+        .ok_or_else(|| api::Error::NotFound("Round not found".to_owned()))
+}
+
+fn rounds(state: &ServiceApiState, query: RoundsRangeQuery) -> ApiResult<Vec<RoundInfo>> {
+    if query.count > MAX_ROUNDS_PER_REQUEST {
+        return Err(api::Error::BadRequest(format!(
+            "Too many rounds requested: {}, maximum is {}",
+            query.count, MAX_ROUNDS_PER_REQUEST
+        )));
+    }
+
+    let schema = RngSchema::new(state.snapshot());
+    let rounds = schema.rounds();
+    let to = rounds.len().min(query.from.saturating_add(query.count));
+
+    Ok((query.from..to).filter_map(|round| rounds.get(round)).collect())
+}
+
+fn request(state: &ServiceApiState, query: RequestQuery) -> ApiResult<RandomnessRequest> {
+    let schema = RngSchema::new(state.snapshot());
+    schema
+        .requests()
+        .get(&query.id)
+        .ok_or_else(|| api::Error::NotFound("Request not found".to_owned()))
+}
+
+fn equivocations(state: &ServiceApiState, query: EquivocationsQuery) -> ApiResult<Vec<Equivocation>> {
+    let schema = RngSchema::new(state.snapshot());
+    Ok(schema
+        .equivocations()
+        .iter()
+        .filter(|equivocation| query.pub_key.map_or(true, |pub_key| *equivocation.pub_key() == pub_key))
+        .collect())
+}
+
+fn validator_stats(state: &ServiceApiState, query: ValidatorStatsQuery) -> ApiResult<Vec<ValidatorParticipation>> {
+    let schema = RngSchema::new(state.snapshot());
+    Ok(schema
+        .validator_stats()
+        .iter()
+        .filter(|&(ref pub_key, _)| query.pub_key.map_or(true, |key| key == *pub_key))
+        .map(|(pub_key, stats)| ValidatorParticipation {
+            pub_key,
+            average_latency: stats.average_latency(),
+            stats,
+        })
+        .collect())
+}
+
+fn round_proof(state: &ServiceApiState, query: RoundQuery) -> ApiResult<RoundProof> {
+    let snapshot = state.snapshot();
+    let core_schema = CoreSchema::new(&snapshot);
+    let schema = RngSchema::new(&snapshot);
+
+    let rounds = schema.rounds();
+    if query.round >= rounds.len() {
+        return Err(api::Error::NotFound("Round not found".to_owned()));
+    }
+
+    let max_height = core_schema.block_hashes_by_height().len() - 1;
+    let block_info = core_schema
+        .block_and_precommits(Height(max_height))
+        .ok_or_else(|| api::Error::InternalError(format_err!("Latest block is not found")))?;
+
+    let to_tables = core_schema.state_hash_aggregator().get_multiproof(vec![
+        Blockchain::service_table_unique_key(SERVICE_ID, ROUND_TABLE_INDEX),
+        Blockchain::service_table_unique_key(SERVICE_ID, ROUNDS_TABLE_INDEX),
+    ]);
+
+    Ok(RoundProof {
+        block_info,
+        to_tables,
+        rounds_len: rounds.len(),
+        to_round: rounds.get_proof(query.round),
+    })
+}
+
+fn metrics(request: HttpRequest) -> FutureResponse {
+    let body = metrics::render(request.state().snapshot());
+    let response = HttpResponse::Ok().content_type(METRICS_CONTENT_TYPE).body(body);
+    Box::new(future::ok(response))
+}
+
 #[derive(Clone)]
 pub struct PublicApi;
 
@@ -25,6 +240,30 @@ impl PublicApi {
     pub fn wire(builder: &mut ServiceApiBuilder) {
         builder
             .public_scope()
+            .endpoint("v1/randomness/latest", latest_randomness)
+            .endpoint("v1/round/current", current_round)
+            .endpoint("v1/rounds/round", round)
+            .endpoint("v1/rounds", rounds)
+            .endpoint("v1/rounds/proof", round_proof)
+            .endpoint("v1/requests/request", request)
+            .endpoint("v1/equivocations", equivocations)
+            .endpoint("v1/validators/stats", validator_stats)
             .endpoint_mut("/tx", post_transaction);
     }
-}
\ No newline at end of file
+}
+
+#[derive(Clone)]
+pub struct PrivateApi;
+
+impl PrivateApi {
+    /// Metrics are served in the Prometheus text format rather than JSON,
+    /// so the endpoint is wired as a raw handler.
+    pub fn wire(builder: &mut ServiceApiBuilder) {
+        let inner: Arc<RawHandler> = Arc::new(metrics);
+        builder.private_scope().web_backend().raw_handler(RequestHandler {
+            name: "v1/metrics".to_owned(),
+            method: Method::GET,
+            inner,
+        });
+    }
+}
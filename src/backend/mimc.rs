@@ -0,0 +1,38 @@
+use rug::Integer;
+use vdf::vdf_mimc::{eval, verify};
+
+use super::{VdfBackend, VdfBackendKind, VdfOutput, VdfParameters};
+
+/// VDF based on the MiMC permutation.
+///
+/// Its verification is only a constant factor faster than evaluation,
+/// so it's kept for compatibility with the networks that started with it.
+///
+/// The permutation is evaluated by the `vdf` crate in a single call, so the evaluation
+/// can't be resumed from checkpoints.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MimcVdf;
+
+impl VdfBackend for MimcVdf {
+    fn eval(&self, challenge: &Integer, difficulty: u64) -> VdfOutput {
+        VdfOutput {
+            value: eval(challenge, difficulty),
+            proof: Vec::new(),
+        }
+    }
+
+    fn verify(&self, challenge: &Integer, difficulty: u64, output: &VdfOutput) -> bool {
+        output.proof.is_empty() && verify(challenge, difficulty, &output.value)
+    }
+
+    fn proof_size(&self, _difficulty: u64) -> usize {
+        0
+    }
+
+    fn parameters(&self) -> VdfParameters {
+        VdfParameters {
+            kind: VdfBackendKind::Mimc,
+            group_bits: 0,
+        }
+    }
+}
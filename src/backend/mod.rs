@@ -0,0 +1,257 @@
+mod mimc;
+mod pietrzak;
+mod rsa;
+mod wesolowski;
+
+pub use self::mimc::MimcVdf;
+pub use self::pietrzak::PietrzakVdf;
+pub use self::wesolowski::WesolowskiVdf;
+
+use rug::Integer;
+
+use blockchain::bigint::BigInt;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Maximal number of elements in a VDF proof accepted from the network.
+pub const MAX_PROOF_ELEMENTS: usize = 64;
+
+/// Output of the VDF evaluation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VdfOutput {
+    /// Value of the VDF.
+    pub value: Integer,
+
+    /// Proof that the value is correct, empty for VDFs without succinct proofs.
+    pub proof: Vec<Integer>,
+}
+
+impl VdfOutput {
+    /// Returns value of the VDF encoded as a `BigInt`, as it's published in transactions.
+    pub fn encoded_value(&self) -> Vec<u8> {
+        integer_to_bytes(&self.value)
+    }
+
+    /// Returns proof encoded as a sequence of `BigInt`s,
+    /// each of them is prefixed with its length in bytes as `u32` in big-endian order.
+    pub fn encoded_proof(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for element in &self.proof {
+            let encoded = integer_to_bytes(element);
+            put_u32_be(&mut bytes, encoded.len() as u32);
+            bytes.extend_from_slice(&encoded);
+        }
+
+        bytes
+    }
+
+    /// Decodes output published in a transaction.
+    ///
+    /// Returns `None` if the value or the proof are malformed,
+    /// including non-canonical or oversized integers.
+    pub fn decode(value: &[u8], proof: &[u8]) -> Option<VdfOutput> {
+        let value = BigInt::decode(value).ok()?.into_integer();
+
+        let mut elements = Vec::new();
+        let mut rest = proof;
+        while !rest.is_empty() {
+            if rest.len() < 4 || elements.len() >= MAX_PROOF_ELEMENTS {
+                return None;
+            }
+
+            let len = rest[..4].iter().fold(0usize, |len, &b| (len << 8) | b as usize);
+            rest = &rest[4..];
+            if rest.len() < len {
+                return None;
+            }
+
+            elements.push(BigInt::decode(&rest[..len]).ok()?.into_integer());
+            rest = &rest[len..];
+        }
+
+        Some(VdfOutput {
+            value,
+            proof: elements,
+        })
+    }
+}
+
+/// Kind of the VDF backend, selected by the service configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VdfBackendKind {
+    /// MiMC-based VDF without succinct proofs.
+    Mimc,
+    /// Wesolowski's VDF over the RSA group.
+    Wesolowski,
+    /// Pietrzak's VDF over the RSA group.
+    Pietrzak,
+}
+
+impl Default for VdfBackendKind {
+    fn default() -> VdfBackendKind {
+        VdfBackendKind::Mimc
+    }
+}
+
+impl VdfBackendKind {
+    /// Creates backend of this kind.
+    pub fn backend(self) -> Box<dyn VdfBackend> {
+        match self {
+            VdfBackendKind::Mimc => Box::new(MimcVdf),
+            VdfBackendKind::Wesolowski => Box::new(WesolowskiVdf::new()),
+            VdfBackendKind::Pietrzak => Box::new(PietrzakVdf::new()),
+        }
+    }
+
+    /// Returns numeric identifier of the kind, as it's stored in the blockchain.
+    pub fn id(self) -> u8 {
+        match self {
+            VdfBackendKind::Mimc => 0,
+            VdfBackendKind::Wesolowski => 1,
+            VdfBackendKind::Pietrzak => 2,
+        }
+    }
+
+    /// Returns kind with the given numeric identifier.
+    pub fn from_id(id: u8) -> Option<VdfBackendKind> {
+        match id {
+            0 => Some(VdfBackendKind::Mimc),
+            1 => Some(VdfBackendKind::Wesolowski),
+            2 => Some(VdfBackendKind::Pietrzak),
+            _ => None,
+        }
+    }
+}
+
+/// Public parameters of the VDF backend.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VdfParameters {
+    /// Kind of the backend.
+    pub kind: VdfBackendKind,
+
+    /// Size of the group modulus in bits, `0` if the backend doesn't use a group of unknown order.
+    pub group_bits: u32,
+}
+
+/// Verifiable delay function.
+///
+/// Implementations should be deterministic, so that every validator
+/// gets the same output and agrees on its validity.
+pub trait VdfBackend: Send + Sync {
+    /// Evaluates VDF of the `challenge` with `difficulty` sequential steps.
+    fn eval(&self, challenge: &Integer, difficulty: u64) -> VdfOutput;
+
+    /// Same as `eval`, but gives up and returns `None` as soon as `cancelled` is set.
+    ///
+    /// Backends that can't be interrupted check the flag only before the evaluation starts.
+    fn eval_cancellable(&self, challenge: &Integer, difficulty: u64, cancelled: &AtomicBool) -> Option<VdfOutput> {
+        if cancelled.load(Ordering::SeqCst) {
+            return None;
+        }
+
+        Some(self.eval(challenge, difficulty))
+    }
+
+    /// Same as `eval_cancellable`, but resumes the sequential part of the evaluation
+    /// from `checkpoints` and saves its progress there.
+    ///
+    /// Backends that can't be resumed ignore `checkpoints`, see `is_resumable`.
+    fn eval_resumable(
+        &self,
+        challenge: &Integer,
+        difficulty: u64,
+        cancelled: &AtomicBool,
+        _checkpoints: &mut dyn Checkpoints,
+    ) -> Option<VdfOutput> {
+        self.eval_cancellable(challenge, difficulty, cancelled)
+    }
+
+    /// Returns `true` if `eval_resumable` saves and restores its progress.
+    fn is_resumable(&self) -> bool {
+        false
+    }
+
+    /// Verifies that `output` is the result of evaluation of the VDF of the `challenge`.
+    fn verify(&self, challenge: &Integer, difficulty: u64, output: &VdfOutput) -> bool;
+
+    /// Returns number of elements in the proof for the given `difficulty`.
+    fn proof_size(&self, difficulty: u64) -> usize;
+
+    /// Returns public parameters of the backend.
+    fn parameters(&self) -> VdfParameters;
+}
+
+/// Storage of the intermediate state of the sequential part of a VDF evaluation,
+/// i.e. the number of squarings done, the value they have produced and the powers
+/// of the input the proof is built from that have been passed so far.
+pub trait Checkpoints {
+    /// Returns the latest saved state as `(iterations, value, powers)`, if any.
+    fn restore(&mut self) -> Option<(u64, Integer, Vec<Integer>)>;
+
+    /// Saves the state after `iterations` squarings.
+    fn save(&mut self, iterations: u64, value: &Integer, powers: &[Integer]);
+
+    /// Returns number of squarings between saves.
+    fn interval(&self) -> u64;
+}
+
+/// Checkpoints of an evaluation that isn't resumed.
+struct NoCheckpoints;
+
+impl Checkpoints for NoCheckpoints {
+    fn restore(&mut self) -> Option<(u64, Integer, Vec<Integer>)> {
+        None
+    }
+
+    fn save(&mut self, _iterations: u64, _value: &Integer, _powers: &[Integer]) {}
+
+    fn interval(&self) -> u64 {
+        u64::max_value()
+    }
+}
+
+/// Encodes non-negative integer as its big-endian magnitude.
+pub fn integer_to_bytes(value: &Integer) -> Vec<u8> {
+    let mut hex = value.to_string_radix(16);
+    if hex == "0" {
+        return Vec::new();
+    }
+    if hex.len() % 2 == 1 {
+        hex.insert(0, '0');
+    }
+
+    hex.as_bytes()
+        .chunks(2)
+        .map(|pair| {
+            let digits = ::std::str::from_utf8(pair).expect("Hex digits are ASCII");
+            u8::from_str_radix(digits, 16).expect("Hex digits are valid")
+        })
+        .collect()
+}
+
+/// Decodes non-negative integer from its big-endian magnitude.
+pub fn integer_from_bytes(bytes: &[u8]) -> Integer {
+    let mut value = Integer::new();
+    for &byte in bytes {
+        value = (value << 8) + byte as u32;
+    }
+
+    value
+}
+
+/// Appends `value` to the `bytes` in big-endian order.
+pub(crate) fn put_u32_be(bytes: &mut Vec<u8>, value: u32) {
+    bytes.extend_from_slice(&[
+        (value >> 24) as u8,
+        (value >> 16) as u8,
+        (value >> 8) as u8,
+        value as u8,
+    ]);
+}
+
+/// Appends `value` to the `bytes` in big-endian order.
+pub(crate) fn put_u64_be(bytes: &mut Vec<u8>, value: u64) {
+    put_u32_be(bytes, (value >> 32) as u32);
+    put_u32_be(bytes, value as u32);
+}
@@ -0,0 +1,218 @@
+use rug::Integer;
+
+use std::{collections::BTreeMap, sync::atomic::AtomicBool};
+
+use super::{rsa, Checkpoints, NoCheckpoints, VdfBackend, VdfBackendKind, VdfOutput, VdfParameters};
+
+/// Maximal number of the first halving steps whose midpoints are assembled from the saved powers,
+/// so at most `2^14` powers of the input are kept in memory.
+const MAX_PRECOMPUTED_STEPS: usize = 14;
+
+/// Pietrzak's VDF over the RSA group of unknown order.
+///
+/// Output is `y = x^(2^T)`. The proof consists of `O(log T)` midpoints `μ = x^(2^(T/2))`,
+/// each of them halves the claim `y = x^(2^T)` into `y' = x'^(2^(T/2))`, where
+/// `x' = x^r * μ`, `y' = μ^r * y` and `r` is a 128-bit challenge derived from `hash(x, y, μ)`.
+/// Odd `T` is turned into even by squaring `y` once.
+///
+/// Input of the `k`-th halving step is `x^E`, where `E = ∏ (r_j + 2^(h_j))` over the previous
+/// steps and `h_j` is the half of the step. So midpoints of the first steps are assembled from
+/// the powers `x^(2^(h_k + Σ h_j))` saved during the evaluation instead of being recomputed,
+/// only the midpoints of the following steps take `T / 2^k` squarings.
+#[derive(Debug, Clone)]
+pub struct PietrzakVdf {
+    modulus: Integer,
+}
+
+impl PietrzakVdf {
+    pub fn new() -> PietrzakVdf {
+        PietrzakVdf {
+            modulus: rsa::modulus(),
+        }
+    }
+
+    /// Makes one halving step of the claim `y = x^(2^t)` with the midpoint `mu`.
+    ///
+    /// Returns the new claim `(x', y', t')` and the challenge `r` it's made with.
+    fn halve(&self, x: &Integer, y: &Integer, t: u64, mu: &Integer) -> (Integer, Integer, u64, Integer) {
+        let (y, t) = if t % 2 == 1 {
+            (rsa::mul(y, y, &self.modulus), t + 1)
+        } else {
+            (y.clone(), t)
+        };
+
+        let r = rsa::challenge(&[x, &y, mu]);
+        let x = rsa::mul(&rsa::pow(x, &r, &self.modulus), mu, &self.modulus);
+        let y = rsa::mul(&rsa::pow(mu, &r, &self.modulus), &y, &self.modulus);
+
+        (x, y, t / 2, r)
+    }
+
+    /// Returns halves `h_k` of the claims made by the halving steps, one per proof element.
+    fn halves(difficulty: u64) -> Vec<u64> {
+        let mut halves = Vec::new();
+        let mut t = difficulty;
+        while t > 1 {
+            t = (t + 1) / 2;
+            halves.push(t);
+        }
+
+        halves
+    }
+
+    /// Returns number of the first halving steps whose midpoints are assembled from the saved powers.
+    ///
+    /// Each of them doubles the number of saved powers and the exponentiations to assemble
+    /// the midpoint, while halving the squarings left for the following midpoints.
+    fn precomputed_steps(difficulty: u64) -> usize {
+        let steps = Self::halves(difficulty).len().min(MAX_PRECOMPUTED_STEPS);
+        let mut precomputed = 0;
+        while precomputed < steps {
+            let next = precomputed as u64 + 1;
+            if (1u64 << (2 * next)).saturating_mul(64 * next) > difficulty {
+                break;
+            }
+            precomputed += 1;
+        }
+
+        precomputed
+    }
+
+    /// Returns position of the power of the input `x^(2^(h_k + Σ h_j))`, where `j`
+    /// are the previous steps selected by the bits of `mask`.
+    fn power_position(halves: &[u64], k: usize, mask: u64) -> u64 {
+        halves[k] + (0..k).filter(|j| mask & (1 << j) != 0).map(|j| halves[j]).sum::<u64>()
+    }
+
+    /// Assembles midpoint `x^(E * 2^(h_k))` of the `k`-th step from the saved powers,
+    /// where `k` is the number of `challenges` of the previous steps.
+    ///
+    /// `E * 2^(h_k)` expands into the sum of `2^(h_k + Σ h_j) * ∏ r_i` over the subsets `j`
+    /// of the previous steps, where `i` are the steps not in the subset.
+    fn assemble_midpoint(&self, halves: &[u64], challenges: &[Integer], powers: &BTreeMap<u64, Integer>) -> Integer {
+        let k = challenges.len();
+        let mut mu = Integer::from(1);
+        for mask in 0..1u64 << k {
+            let mut exponent = Integer::from(1);
+            for (i, r) in challenges.iter().enumerate() {
+                if mask & (1 << i) == 0 {
+                    exponent *= r;
+                }
+            }
+
+            let power = &powers[&Self::power_position(halves, k, mask)];
+            mu = rsa::mul(&mu, &rsa::pow(power, &exponent, &self.modulus), &self.modulus);
+        }
+
+        mu
+    }
+}
+
+impl Default for PietrzakVdf {
+    fn default() -> PietrzakVdf {
+        PietrzakVdf::new()
+    }
+}
+
+impl VdfBackend for PietrzakVdf {
+    fn eval(&self, challenge: &Integer, difficulty: u64) -> VdfOutput {
+        self.eval_cancellable(challenge, difficulty, &AtomicBool::new(false))
+            .expect("Evaluation can't be cancelled")
+    }
+
+    fn eval_cancellable(&self, challenge: &Integer, difficulty: u64, cancelled: &AtomicBool) -> Option<VdfOutput> {
+        self.eval_resumable(challenge, difficulty, cancelled, &mut NoCheckpoints)
+    }
+
+    fn eval_resumable(
+        &self,
+        challenge: &Integer,
+        difficulty: u64,
+        cancelled: &AtomicBool,
+        checkpoints: &mut dyn Checkpoints,
+    ) -> Option<VdfOutput> {
+        let x = rsa::hash_to_group(challenge, &self.modulus);
+        let halves = Self::halves(difficulty);
+        let precomputed = Self::precomputed_steps(difficulty);
+        let mut positions = (0..precomputed)
+            .flat_map(|k| (0..1u64 << k).map(move |mask| (k, mask)))
+            .map(|(k, mask)| Self::power_position(&halves, k, mask))
+            .collect::<Vec<_>>();
+        positions.sort();
+        positions.dedup();
+
+        // Due to rounding up of odd halves, the last positions may be a few squarings past `T`
+        let split = positions.iter().position(|&p| p > difficulty).unwrap_or_else(|| positions.len());
+        let (value, chain) = rsa::square_times_with_powers(
+            &x,
+            difficulty,
+            &positions[..split],
+            &self.modulus,
+            cancelled,
+            checkpoints,
+        )?;
+        let mut powers = positions.iter().cloned().zip(chain).collect::<BTreeMap<_, _>>();
+        for &position in &positions[split..] {
+            let power = rsa::square_times(&value, position - difficulty, &self.modulus, cancelled)?;
+            powers.insert(position, power);
+        }
+
+        let mut proof = Vec::with_capacity(halves.len());
+        let mut challenges = Vec::with_capacity(precomputed);
+        let (mut x, mut y, mut t) = (x, value.clone(), difficulty);
+        for (k, &half) in halves.iter().enumerate() {
+            let mu = if k < precomputed {
+                self.assemble_midpoint(&halves, &challenges, &powers)
+            } else {
+                rsa::square_times(&x, half, &self.modulus, cancelled)?
+            };
+
+            let (next_x, next_y, next_t, r) = self.halve(&x, &y, t, &mu);
+            x = next_x;
+            y = next_y;
+            t = next_t;
+            challenges.push(r);
+            proof.push(mu);
+        }
+
+        Some(VdfOutput { value, proof })
+    }
+
+    fn is_resumable(&self) -> bool {
+        true
+    }
+
+    fn verify(&self, challenge: &Integer, difficulty: u64, output: &VdfOutput) -> bool {
+        if difficulty == 0 || output.proof.len() != self.proof_size(difficulty) {
+            return false;
+        }
+
+        if !rsa::is_element(&output.value, &self.modulus)
+            || !output.proof.iter().all(|mu| rsa::is_element(mu, &self.modulus))
+        {
+            return false;
+        }
+
+        let x = rsa::hash_to_group(challenge, &self.modulus);
+        let (mut x, mut y, mut t) = (x, output.value.clone(), difficulty);
+        for mu in &output.proof {
+            let (next_x, next_y, next_t, _) = self.halve(&x, &y, t, mu);
+            x = next_x;
+            y = next_y;
+            t = next_t;
+        }
+
+        t == 1 && y == rsa::mul(&x, &x, &self.modulus)
+    }
+
+    fn proof_size(&self, difficulty: u64) -> usize {
+        Self::halves(difficulty).len()
+    }
+
+    fn parameters(&self) -> VdfParameters {
+        VdfParameters {
+            kind: VdfBackendKind::Pietrzak,
+            group_bits: self.modulus.significant_bits(),
+        }
+    }
+}
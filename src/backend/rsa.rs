@@ -0,0 +1,277 @@
+use exonum::crypto::{hash, Hash};
+use rug::Integer;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use super::Checkpoints;
+
+/// Number of squarings between checks of the cancellation flag.
+const CANCELLATION_CHECK_INTERVAL: u64 = 1 << 12;
+
+/// Decimal digits of the RSA-2048 challenge modulus, whose factorization is unknown.
+const RSA_2048_DIGITS: [&str; 8] = [
+    "2519590847565789349402718324004839857142928212620403202777713783604366202070759555626401",
+    "8525880784406918290641249515082189298559149176184502808489120072844992687392807287776735",
+    "9714183472702618963750149718246911650776133798590957000973304597488084284017974291006424",
+    "5869181719511874612151517265463228221686998754918242243363725908514186546204357679842338",
+    "7184774447920739934236584823824281198163815010674810451660377306056201619676256133844143",
+    "6038339044149526344321901146575444541784240209246165157233507787077498171257724679629263",
+    "8635637328991215483143816789988504044536402352738195137863656439121201039712282212072035",
+    "7",
+];
+
+/// Returns RSA-2048 challenge modulus.
+pub fn modulus() -> Integer {
+    let digits = RSA_2048_DIGITS.concat();
+    Integer::from_str_radix(&digits, 10).expect("RSA-2048 modulus is a valid integer")
+}
+
+/// Hashes a sequence of integers into a 256-bit value.
+///
+/// Each integer is encoded as its big-endian magnitude, prefixed with the length in bytes
+/// as `u32` in big-endian order, so that different sequences can't collide.
+pub fn hash_integers(values: &[&Integer]) -> Hash {
+    let mut bytes = Vec::new();
+    for value in values {
+        let encoded = super::integer_to_bytes(value);
+        super::put_u32_be(&mut bytes, encoded.len() as u32);
+        bytes.extend_from_slice(&encoded);
+    }
+
+    hash(&bytes)
+}
+
+/// Converts a hash into a non-negative integer.
+pub fn hash_to_integer(value: &Hash) -> Integer {
+    Integer::from_str_radix(&value.to_hex(), 16).expect("Hex of the hash is a valid integer")
+}
+
+/// Returns 128-bit challenge derived from the hash of the `values`.
+pub fn challenge(values: &[&Integer]) -> Integer {
+    hash_to_integer(&hash_integers(values)) >> 128
+}
+
+/// Maps VDF input onto an element of the RSA group.
+///
+/// The input is expanded to the size of the modulus by hashing it with a counter.
+pub fn hash_to_group(input: &Integer, modulus: &Integer) -> Integer {
+    let bits = modulus.significant_bits();
+    let mut element = Integer::new();
+    let mut counter = 0u32;
+    while element.significant_bits() < bits + 128 {
+        let chunk = hash_to_integer(&hash_integers(&[&Integer::from(counter), input]));
+        element = (element << 256) + chunk;
+        counter += 1;
+    }
+
+    let element = element % modulus;
+    if element <= 1 {
+        // Practically unreachable, but 0 and 1 are fixed points of squaring.
+        Integer::from(2)
+    } else {
+        element
+    }
+}
+
+/// Squares `value` in the group `times` times in a row.
+///
+/// Returns `None` if `cancelled` has been set before all squarings are done.
+pub fn square_times(value: &Integer, times: u64, modulus: &Integer, cancelled: &AtomicBool) -> Option<Integer> {
+    #[cfg(test)]
+    tests::SQUARINGS.with(|squarings| squarings.set(squarings.get() + times));
+
+    let mut value = value.clone();
+    for i in 0..times {
+        if i % CANCELLATION_CHECK_INTERVAL == 0 && cancelled.load(Ordering::SeqCst) {
+            return None;
+        }
+
+        value.square_mut();
+        value %= modulus;
+    }
+
+    Some(value)
+}
+
+/// Same as `square_times`, but starts from the state restored from `checkpoints`,
+/// if it's not further than `times` squarings, and saves the state every `interval` squarings.
+pub fn square_times_resumable(
+    value: &Integer,
+    times: u64,
+    modulus: &Integer,
+    cancelled: &AtomicBool,
+    checkpoints: &mut dyn Checkpoints,
+) -> Option<Integer> {
+    square_times_with_powers(value, times, &[], modulus, cancelled, checkpoints).map(|(value, _)| value)
+}
+
+/// Same as `square_times_resumable`, but also returns `value^(2^p)` for every `p`
+/// in `positions`, which should be sorted and not exceed `times`.
+///
+/// Powers passed before the checkpoint are saved along with it. If the restored powers
+/// don't match `positions`, they are recovered by squaring `value` once again,
+/// that takes as long as the squarings that were restored.
+pub fn square_times_with_powers(
+    value: &Integer,
+    times: u64,
+    positions: &[u64],
+    modulus: &Integer,
+    cancelled: &AtomicBool,
+    checkpoints: &mut dyn Checkpoints,
+) -> Option<(Integer, Vec<Integer>)> {
+    debug_assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+    debug_assert!(positions.last().map_or(true, |&last| last <= times));
+
+    let (mut done, mut current, restored) = match checkpoints.restore() {
+        Some((iterations, saved, powers)) if iterations <= times && is_element(&saved, modulus) => {
+            (iterations, saved, powers)
+        }
+        _ => (0, value.clone(), Vec::new()),
+    };
+
+    let passed = positions.iter().take_while(|&&position| position < done).count();
+    let is_restored = restored.len() == passed && restored.iter().all(|power| is_element(power, modulus));
+    let mut powers = Vec::with_capacity(positions.len());
+    let mut positions = positions.iter().cloned().peekable();
+    if is_restored {
+        powers.extend(restored);
+        for _ in 0..passed {
+            positions.next();
+        }
+    }
+
+    let (mut recovered, mut power) = (0, value.clone());
+    while let Some(&position) = positions.peek() {
+        if position >= done {
+            break;
+        }
+
+        power = square_times(&power, position - recovered, modulus, cancelled)?;
+        recovered = position;
+        powers.push(power.clone());
+        positions.next();
+    }
+
+    let interval = checkpoints.interval().max(1);
+    loop {
+        if positions.peek() == Some(&done) {
+            powers.push(current.clone());
+            positions.next();
+        }
+        if done >= times {
+            break;
+        }
+
+        let mut steps = (interval - done % interval).min(times - done);
+        if let Some(&position) = positions.peek() {
+            steps = steps.min(position - done);
+        }
+
+        current = square_times(&current, steps, modulus, cancelled)?;
+        done += steps;
+        if done % interval == 0 || done == times {
+            checkpoints.save(done, &current, &powers);
+        }
+    }
+
+    Some((current, powers))
+}
+
+/// Raises `base` to the power of `exponent` in the group.
+pub fn pow(base: &Integer, exponent: &Integer, modulus: &Integer) -> Integer {
+    base.clone()
+        .pow_mod(exponent, modulus)
+        .expect("Exponent is non-negative")
+}
+
+/// Multiplies two elements of the group.
+pub fn mul(a: &Integer, b: &Integer, modulus: &Integer) -> Integer {
+    Integer::from(a * b) % modulus
+}
+
+/// Checks that `value` is a non-trivial element of the group.
+pub fn is_element(value: &Integer, modulus: &Integer) -> bool {
+    *value > 1 && value < modulus
+}
+
+#[cfg(test)]
+mod tests {
+    use rug::Integer;
+
+    use std::{cell::Cell, sync::atomic::AtomicBool};
+
+    use super::{modulus, square_times_with_powers};
+    use backend::Checkpoints;
+
+    thread_local! {
+        /// Number of squarings performed by `square_times` in the current thread.
+        pub static SQUARINGS: Cell<u64> = Cell::new(0);
+    }
+
+    /// Checkpoints kept in memory.
+    struct MemoryCheckpoints {
+        saved: Option<(u64, Integer, Vec<Integer>)>,
+        interval: u64,
+    }
+
+    impl Checkpoints for MemoryCheckpoints {
+        fn restore(&mut self) -> Option<(u64, Integer, Vec<Integer>)> {
+            self.saved.clone()
+        }
+
+        fn save(&mut self, iterations: u64, value: &Integer, powers: &[Integer]) {
+            self.saved = Some((iterations, value.clone(), powers.to_vec()));
+        }
+
+        fn interval(&self) -> u64 {
+            self.interval
+        }
+    }
+
+    fn count_squarings<T, F: FnOnce() -> T>(f: F) -> (T, u64) {
+        SQUARINGS.with(|squarings| squarings.set(0));
+        let result = f();
+        (result, SQUARINGS.with(Cell::get))
+    }
+
+    #[test]
+    fn test_resumed_evaluation_keeps_powers() {
+        let modulus = modulus();
+        let value = Integer::from(3);
+        let positions = (0..1000).step_by(100).collect::<Vec<u64>>();
+        let not_cancelled = AtomicBool::new(false);
+        let mut checkpoints = MemoryCheckpoints {
+            saved: None,
+            interval: 300,
+        };
+
+        let mut fresh = MemoryCheckpoints {
+            saved: None,
+            interval: 300,
+        };
+        let (expected, squarings) = count_squarings(|| {
+            square_times_with_powers(&value, 1000, &positions, &modulus, &not_cancelled, &mut fresh)
+        });
+        assert_eq!(squarings, 1000);
+
+        // Evaluation is interrupted after 500 squarings
+        let passed = &positions[..6];
+        square_times_with_powers(&value, 500, passed, &modulus, &not_cancelled, &mut checkpoints).unwrap();
+        assert_eq!(checkpoints.saved.as_ref().map(|saved| saved.2.len()), Some(5));
+
+        // Resumed evaluation performs only the remaining squarings
+        let (output, squarings) = count_squarings(|| {
+            square_times_with_powers(&value, 1000, &positions, &modulus, &not_cancelled, &mut checkpoints)
+        });
+        assert_eq!(output, expected);
+        assert_eq!(squarings, 500);
+
+        // Powers missing from the checkpoint are recovered by squaring the value again
+        checkpoints.saved = Some((500, expected.as_ref().unwrap().1[5].clone(), Vec::new()));
+        let (output, squarings) = count_squarings(|| {
+            square_times_with_powers(&value, 1000, &positions, &modulus, &not_cancelled, &mut checkpoints)
+        });
+        assert_eq!(output, expected);
+        assert_eq!(squarings, 400 + 500);
+    }
+}
@@ -0,0 +1,184 @@
+use rug::Integer;
+
+use std::sync::atomic::AtomicBool;
+
+use super::{rsa, Checkpoints, NoCheckpoints, VdfBackend, VdfBackendKind, VdfOutput, VdfParameters};
+
+/// Maximal number of powers of the input kept in memory to compute the proof.
+const MAX_PROOF_POWERS: u64 = 1 << 14;
+
+/// Maximal size of the digits the proof is assembled from, in bits.
+const MAX_DIGIT_BITS: u64 = 16;
+
+/// Wesolowski's VDF over the RSA group of unknown order.
+///
+/// Output is `y = x^(2^T)`, the proof is a single group element `π = x^⌊2^T / l⌋`,
+/// where `l` is a 128-bit prime derived from `hash(x, y)`. Verification checks
+/// `π^l * x^(2^T mod l) = y`, that takes only `O(log T)` group operations.
+///
+/// The proof is assembled from `κ`-bit digits of `⌊2^T / l⌋` and every `κγ`-th power
+/// of `x` saved during the evaluation, so it takes about `T / κ` group operations
+/// instead of another `T` squarings, while keeping at most `MAX_PROOF_POWERS` powers in memory.
+#[derive(Debug, Clone)]
+pub struct WesolowskiVdf {
+    modulus: Integer,
+}
+
+impl WesolowskiVdf {
+    pub fn new() -> WesolowskiVdf {
+        WesolowskiVdf {
+            modulus: rsa::modulus(),
+        }
+    }
+
+    fn prime_challenge(x: &Integer, y: &Integer) -> Integer {
+        rsa::challenge(&[x, y]).next_prime()
+    }
+
+    /// Returns `(κ, γ)` for the `difficulty`: the proof is assembled from `κ`-bit digits
+    /// of `⌊2^T / l⌋`, and every `κγ`-th power of the input is saved during the evaluation.
+    fn proof_parameters(difficulty: u64) -> (u64, u64) {
+        let bits = 64 - u64::from(difficulty.leading_zeros());
+        let kappa = (bits / 3).max(1).min(MAX_DIGIT_BITS);
+        let gamma = (difficulty + kappa * MAX_PROOF_POWERS - 1) / (kappa * MAX_PROOF_POWERS);
+        (kappa, gamma.max(1))
+    }
+
+    /// Calculates `π = x^⌊2^T / l⌋` from `powers[m] = x^(2^(κγm))`.
+    ///
+    /// `⌊2^T / l⌋ = Σ b_i * 2^(κi)`, where the digit `b_i = ⌊2^κ * (2^(T - κ(i + 1)) mod l) / l⌋`
+    /// is found without calculating the quotient itself. Digits `b_(γm + j)` for each `j`
+    /// are handled in one pass: powers are multiplied into buckets by their digit `b`,
+    /// buckets are raised to `b` and the passes are joined by `κ` squarings in between.
+    fn proof(
+        &self,
+        powers: &[Integer],
+        l: &Integer,
+        difficulty: u64,
+        cancelled: &AtomicBool,
+    ) -> Option<Integer> {
+        let (kappa, gamma) = Self::proof_parameters(difficulty);
+        let digits = (difficulty + kappa - 1) / kappa;
+        let two = Integer::from(2);
+        let shift = rsa::pow(&two, &Integer::from(kappa * gamma), l);
+
+        let mut pi = Integer::from(1);
+        for j in (0..gamma).rev() {
+            pi = rsa::square_times(&pi, kappa, &self.modulus, cancelled)?;
+
+            let mut buckets = vec![Integer::from(1); 1 << kappa];
+            let mut remainder: Option<Integer> = None;
+            for (m, power) in powers.iter().enumerate().rev() {
+                let i = gamma * m as u64 + j;
+                if i >= digits {
+                    continue;
+                }
+
+                let digit = if kappa * (i + 1) > difficulty {
+                    (Integer::from(1) << (difficulty - kappa * i) as u32) / l
+                } else {
+                    // Remainders of the consecutive digits of the pass differ by `2^(κγ)`
+                    let r = match remainder.take() {
+                        Some(r) => (r * &shift) % l,
+                        None => rsa::pow(&two, &Integer::from(difficulty - kappa * (i + 1)), l),
+                    };
+                    let digit = Integer::from(&r << kappa as u32) / l;
+                    remainder = Some(r);
+                    digit
+                };
+
+                let digit = digit.to_usize().expect("Digit is less than 2^κ");
+                if digit != 0 {
+                    buckets[digit] = rsa::mul(&buckets[digit], power, &self.modulus);
+                }
+            }
+
+            // Product of `buckets[b]^b` as the product of the suffix products
+            let mut suffix = Integer::from(1);
+            for bucket in buckets.iter().skip(1).rev() {
+                suffix = rsa::mul(&suffix, bucket, &self.modulus);
+                pi = rsa::mul(&pi, &suffix, &self.modulus);
+            }
+        }
+
+        Some(pi)
+    }
+}
+
+impl Default for WesolowskiVdf {
+    fn default() -> WesolowskiVdf {
+        WesolowskiVdf::new()
+    }
+}
+
+impl VdfBackend for WesolowskiVdf {
+    fn eval(&self, challenge: &Integer, difficulty: u64) -> VdfOutput {
+        self.eval_cancellable(challenge, difficulty, &AtomicBool::new(false))
+            .expect("Evaluation can't be cancelled")
+    }
+
+    fn eval_cancellable(&self, challenge: &Integer, difficulty: u64, cancelled: &AtomicBool) -> Option<VdfOutput> {
+        self.eval_resumable(challenge, difficulty, cancelled, &mut NoCheckpoints)
+    }
+
+    fn eval_resumable(
+        &self,
+        challenge: &Integer,
+        difficulty: u64,
+        cancelled: &AtomicBool,
+        checkpoints: &mut dyn Checkpoints,
+    ) -> Option<VdfOutput> {
+        let x = rsa::hash_to_group(challenge, &self.modulus);
+        let (kappa, gamma) = Self::proof_parameters(difficulty);
+        let positions = (0..difficulty)
+            .step_by((kappa * gamma) as usize)
+            .collect::<Vec<_>>();
+        let (y, powers) =
+            rsa::square_times_with_powers(&x, difficulty, &positions, &self.modulus, cancelled, checkpoints)?;
+
+        let l = Self::prime_challenge(&x, &y);
+        let pi = self.proof(&powers, &l, difficulty, cancelled)?;
+
+        Some(VdfOutput {
+            value: y,
+            proof: vec![pi],
+        })
+    }
+
+    fn is_resumable(&self) -> bool {
+        true
+    }
+
+    fn verify(&self, challenge: &Integer, difficulty: u64, output: &VdfOutput) -> bool {
+        if output.proof.len() != 1 {
+            return false;
+        }
+
+        let (y, pi) = (&output.value, &output.proof[0]);
+        if !rsa::is_element(y, &self.modulus) || !rsa::is_element(pi, &self.modulus) {
+            return false;
+        }
+
+        let x = rsa::hash_to_group(challenge, &self.modulus);
+        let l = Self::prime_challenge(&x, y);
+        let r = rsa::pow(&Integer::from(2), &Integer::from(difficulty), &l);
+
+        let lhs = rsa::mul(
+            &rsa::pow(pi, &l, &self.modulus),
+            &rsa::pow(&x, &r, &self.modulus),
+            &self.modulus,
+        );
+        lhs == *y
+    }
+
+    fn proof_size(&self, _difficulty: u64) -> usize {
+        1
+    }
+
+    fn parameters(&self) -> VdfParameters {
+        VdfParameters {
+            kind: VdfBackendKind::Wesolowski,
+            group_bits: self.modulus.significant_bits(),
+        }
+    }
+}
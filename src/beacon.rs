@@ -0,0 +1,107 @@
+use exonum::{crypto::Hash, helpers::Height, storage::Snapshot};
+
+use blockchain::schema::{RandomnessRequest, RngSchema, RoundInfo};
+use rng::RandomStream;
+
+/// Output of a successfully finalized round.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoundOutput {
+    /// Number of the round.
+    pub round: u64,
+
+    /// Combined seed of the round.
+    pub seed: Hash,
+
+    /// Randomness of the round.
+    pub randomness: Hash,
+
+    /// Height of the block in which the round was finalized.
+    pub height: Height,
+}
+
+impl RoundOutput {
+    /// Returns stream of random values derived from the randomness of the round
+    /// for the given `label` and `index`.
+    pub fn stream(&self, label: &[u8], index: u64) -> RandomStream {
+        RandomStream::new(&self.randomness, label, index)
+    }
+
+    fn from_info(info: &RoundInfo) -> Option<RoundOutput> {
+        if info.failed() {
+            return None;
+        }
+
+        Some(RoundOutput {
+            round: info.round(),
+            seed: *info.seed(),
+            randomness: *info.randomness(),
+            height: info.height(),
+        })
+    }
+}
+
+/// Read-only access to the beacon outputs for other services.
+///
+/// Outputs are read from the blockchain state, so they are the same on every node
+/// and can be used in transactions of other services, e.g. `Beacon::new(&*fork)`.
+/// Randomness of a future round can be requested with `TxRequestRandomness`,
+/// the request is fulfilled with the first successfully finalized round not earlier
+/// than the requested one.
+#[derive(Debug)]
+pub struct Beacon<T> {
+    schema: RngSchema<T>,
+}
+
+impl<T> Beacon<T>
+where
+    T: AsRef<Snapshot>,
+{
+    /// Creates beacon over a snapshot or a fork of the blockchain state.
+    pub fn new(view: T) -> Beacon<T> {
+        Beacon {
+            schema: RngSchema::new(view),
+        }
+    }
+
+    /// Returns number of the round that is in progress.
+    ///
+    /// Outputs are available only for the earlier rounds.
+    pub fn current_round(&self) -> u64 {
+        self.schema.current_round()
+    }
+
+    /// Returns output of the `round`.
+    ///
+    /// Returns `None` if the round isn't finalized yet or has failed.
+    pub fn output(&self, round: u64) -> Option<RoundOutput> {
+        self.schema
+            .round_info(round)
+            .and_then(|info| RoundOutput::from_info(&info))
+    }
+
+    /// Returns output of the first successfully finalized round not earlier than `round`.
+    ///
+    /// Returns `None` if there's no such round yet.
+    pub fn output_at_or_after(&self, round: u64) -> Option<RoundOutput> {
+        let rounds = self.schema.rounds();
+        (round..rounds.len())
+            .filter_map(|round| rounds.get(round))
+            .filter_map(|info| RoundOutput::from_info(&info))
+            .next()
+    }
+
+    /// Returns the latest successfully finalized round output.
+    pub fn latest_output(&self) -> Option<RoundOutput> {
+        let rounds = self.schema.rounds();
+        (0..rounds.len())
+            .rev()
+            .filter_map(|round| rounds.get(round))
+            .filter_map(|info| RoundOutput::from_info(&info))
+            .next()
+    }
+
+    /// Returns request registered by the `TxRequestRandomness` transaction with the given hash.
+    pub fn request(&self, tx_hash: &Hash) -> Option<RandomnessRequest> {
+        self.schema.requests().get(tx_hash)
+    }
+}
@@ -0,0 +1,275 @@
+extern crate clap;
+extern crate exonum;
+extern crate exonum_rng;
+#[macro_use]
+extern crate failure;
+extern crate reqwest;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_json;
+
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use exonum::{
+    crypto::{Hash, PublicKey},
+    encoding::serialize::FromHex,
+};
+use failure::Error;
+use serde::{de::DeserializeOwned, Serialize};
+
+use std::process;
+
+use exonum_rng::{
+    api::{RoundStatus, MAX_ROUNDS_PER_REQUEST},
+    backend::{VdfBackendKind, VdfOutput},
+    export::{rounds_to_csv, rounds_to_json},
+    blockchain::{
+        config::{ContributionSource, DEFAULT_VDF_DIFFICULTY},
+        schema::RoundInfo,
+    },
+    rng::calculate_vdf,
+    verify::verify_round,
+    SERVICE_NAME,
+};
+
+/// Address of the public API of a node used when `--node` is not specified.
+const DEFAULT_NODE: &str = "http://127.0.0.1:8200";
+
+/// VDF output calculated offline, encoded as it's published in `TxPublishVdfResult`.
+#[derive(Debug, Serialize)]
+struct VdfResult {
+    /// Value as a decimal string.
+    value: String,
+    /// Value encoded with `BigInt::to_bytes` as a hex string.
+    encoded_value: String,
+    /// Proof encoded with `VdfOutput::encoded_proof` as a hex string.
+    encoded_proof: String,
+}
+
+/// Client of the public HTTP API of a node.
+struct Client {
+    node: String,
+    http: reqwest::Client,
+}
+
+impl Client {
+    fn new(node: &str) -> Client {
+        Client {
+            node: node.trim_end_matches('/').to_owned(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    fn get<T: DeserializeOwned>(&self, path: &str, query: &[(&str, String)]) -> Result<T, Error> {
+        let url = format!("{}/api/{}", self.node, path);
+        let mut response = self.http.get(&url).query(query).send()?;
+        if !response.status().is_success() {
+            let message = response.text().unwrap_or_default();
+            bail!("{} returned {}: {}", url, response.status(), message);
+        }
+
+        Ok(response.json()?)
+    }
+
+    fn service<T: DeserializeOwned>(&self, endpoint: &str, query: &[(&str, String)]) -> Result<T, Error> {
+        self.get(&format!("services/{}/{}", SERVICE_NAME, endpoint), query)
+    }
+
+    fn status(&self) -> Result<RoundStatus, Error> {
+        self.service("v1/round/current", &[])
+    }
+
+    fn latest(&self) -> Result<RoundInfo, Error> {
+        self.service("v1/randomness/latest", &[])
+    }
+
+    fn round(&self, round: u64) -> Result<RoundInfo, Error> {
+        self.service("v1/rounds/round", &[("round", round.to_string())])
+    }
+
+    /// Fetches rounds `[from, to)` page by page.
+    fn rounds(&self, from: u64, to: u64) -> Result<Vec<RoundInfo>, Error> {
+        let mut rounds = Vec::new();
+        let mut next = from;
+        while next < to {
+            let count = (to - next).min(MAX_ROUNDS_PER_REQUEST);
+            let page: Vec<RoundInfo> = self.service(
+                "v1/rounds",
+                &[("from", next.to_string()), ("count", count.to_string())],
+            )?;
+            if page.is_empty() {
+                break;
+            }
+
+            next += page.len() as u64;
+            rounds.extend(page);
+        }
+
+        Ok(rounds)
+    }
+}
+
+fn print_json<T: Serialize>(value: &T) -> Result<(), Error> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn parse_u64(matches: &ArgMatches, name: &str) -> Result<u64, Error> {
+    let value = matches.value_of(name).expect("Argument has a default value or is required");
+    value
+        .parse()
+        .map_err(|_| format_err!("Invalid value of {}: {}", name, value))
+}
+
+fn verify(client: &Client, matches: &ArgMatches) -> Result<bool, Error> {
+    // Node may be untrusted, so the round is checked against the validator set
+    // and the contribution source given by the user rather than the ones it reports.
+    let validators = matches
+        .values_of("validator")
+        .expect("Validators are required")
+        .map(|key| PublicKey::from_hex(key).map_err(|_| format_err!("Invalid key: {}", key)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let source_name = matches.value_of("contribution-source").expect("Source has a default value");
+    let source: ContributionSource = serde_json::from_value(source_name.into())
+        .map_err(|_| format_err!("Unknown contribution source: {}", source_name))?;
+
+    let info = client.round(parse_u64(matches, "ROUND")?)?;
+    let verdict = verify_round(&info, &validators, source);
+    print_json(&verdict)?;
+    Ok(verdict.is_valid())
+}
+
+fn vdf(matches: &ArgMatches) -> Result<(), Error> {
+    let seed_hex = matches.value_of("SEED").expect("Seed is required");
+    let seed = Hash::from_hex(seed_hex).map_err(|_| format_err!("Invalid seed: {}", seed_hex))?;
+    let difficulty = parse_u64(matches, "difficulty")?;
+    let backend_name = matches.value_of("backend").expect("Backend has a default value");
+    let kind: VdfBackendKind = serde_json::from_value(backend_name.into())
+        .map_err(|_| format_err!("Unknown VDF backend: {}", backend_name))?;
+
+    let output: VdfOutput = calculate_vdf(&*kind.backend(), &seed, difficulty)
+        .ok_or_else(|| format_err!("Seed can't be converted into a VDF challenge"))?;
+
+    print_json(&VdfResult {
+        value: output.value.to_string(),
+        encoded_value: to_hex(&output.encoded_value()),
+        encoded_proof: to_hex(&output.encoded_proof()),
+    })
+}
+
+fn run(matches: &ArgMatches) -> Result<bool, Error> {
+    let client = Client::new(matches.value_of("node").unwrap_or(DEFAULT_NODE));
+
+    match matches.subcommand() {
+        ("status", _) => print_json(&client.status()?)?,
+        ("latest", _) => print_json(&client.latest()?)?,
+        ("round", Some(matches)) => print_json(&client.round(parse_u64(matches, "ROUND")?)?)?,
+        ("export", Some(matches)) => {
+            let from = parse_u64(matches, "from")?;
+            let to = match matches.value_of("to") {
+                Some(_) => parse_u64(matches, "to")?,
+                None => client.status()?.round,
+            };
+
+            let rounds = client.rounds(from, to)?;
+            match matches.value_of("format") {
+                Some("csv") => print!("{}", rounds_to_csv(&rounds)),
+                _ => println!("{}", rounds_to_json(&rounds)?),
+            }
+        }
+        ("verify", Some(matches)) => return verify(&client, matches),
+        ("vdf", Some(matches)) => vdf(matches)?,
+        _ => unreachable!("Subcommand is required"),
+    }
+
+    Ok(true)
+}
+
+fn main() {
+    let default_difficulty = DEFAULT_VDF_DIFFICULTY.to_string();
+    let round_arg = Arg::with_name("ROUND")
+        .help("Number of the round")
+        .required(true);
+
+    let matches = App::new("exonum-rng-cli")
+        .about("Queries and verifies the exonum-rng randomness beacon")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .arg(
+            Arg::with_name("node")
+                .long("node")
+                .takes_value(true)
+                .global(true)
+                .help("Address of the public API of the node, http://127.0.0.1:8200 by default"),
+        )
+        .subcommand(SubCommand::with_name("status").about("Prints status of the current round"))
+        .subcommand(SubCommand::with_name("latest").about("Prints the latest finalized round"))
+        .subcommand(
+            SubCommand::with_name("round")
+                .about("Prints the round with the given number")
+                .arg(round_arg.clone()),
+        )
+        .subcommand(
+            SubCommand::with_name("export")
+                .about("Exports rounds from the history")
+                .arg(Arg::with_name("from").long("from").default_value("0"))
+                .arg(
+                    Arg::with_name("to")
+                        .long("to")
+                        .takes_value(true)
+                        .help("Round to stop before, the current round by default"),
+                )
+                .arg(
+                    Arg::with_name("format")
+                        .long("format")
+                        .possible_values(&["json", "csv"])
+                        .default_value("json"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("verify")
+                .about("Recomputes seed, VDF and randomness of the round locally")
+                .arg(round_arg)
+                .arg(
+                    Arg::with_name("validator")
+                        .long("validator")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .required(true)
+                        .help("Service key of a validator the round is expected to be made by"),
+                )
+                .arg(
+                    Arg::with_name("contribution-source")
+                        .long("contribution-source")
+                        .possible_values(&["os", "signature"])
+                        .default_value("os")
+                        .help("Source of contributions the round is expected to be made with"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("vdf")
+                .about("Calculates VDF of the seed offline")
+                .arg(Arg::with_name("SEED").help("Seed as a hex string").required(true))
+                .arg(Arg::with_name("difficulty").long("difficulty").default_value(&default_difficulty))
+                .arg(
+                    Arg::with_name("backend")
+                        .long("backend")
+                        .possible_values(&["mimc", "wesolowski", "pietrzak"])
+                        .default_value("mimc"),
+                ),
+        )
+        .get_matches();
+
+    match run(&matches) {
+        Ok(true) => {}
+        Ok(false) => process::exit(1),
+        Err(e) => {
+            eprintln!("error: {}", e);
+            process::exit(2);
+        }
+    }
+}
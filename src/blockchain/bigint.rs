@@ -0,0 +1,141 @@
+use exonum::{
+    crypto::{hash, CryptoHash, Hash},
+    storage::StorageValue,
+};
+use rug::Integer;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use std::{borrow::Cow, fmt, str::FromStr};
+
+use backend::{integer_from_bytes, integer_to_bytes};
+
+/// Maximal size of a big integer accepted from the network, in bytes.
+///
+/// Fits elements of RSA groups with moduli up to 4096 bits.
+pub const MAX_BIG_INT_BYTES: usize = 512;
+
+/// Reasons for a big integer to be rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Fail)]
+pub enum BigIntError {
+    /// Value is not a decimal number.
+    #[fail(display = "Value is not a decimal number.")]
+    Malformed,
+
+    /// Value is negative.
+    #[fail(display = "Value is negative.")]
+    Negative,
+
+    /// Value is larger than `MAX_BIG_INT_BYTES`.
+    #[fail(display = "Value is too large.")]
+    TooLarge,
+
+    /// Binary encoding of the value has leading zero bytes.
+    #[fail(display = "Binary encoding of the value is not canonical.")]
+    NonCanonical,
+}
+
+/// Non-negative big integer of at most `MAX_BIG_INT_BYTES` bytes.
+///
+/// It's encoded as its big-endian magnitude without leading zero bytes, so every value
+/// has exactly one binary encoding, and zero is encoded as an empty byte string.
+/// In JSON it's represented as a decimal string.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct BigInt(Integer);
+
+impl BigInt {
+    /// Wraps `value`, checking that it's non-negative and fits into `MAX_BIG_INT_BYTES`.
+    pub fn new(value: Integer) -> Result<BigInt, BigIntError> {
+        if value < 0 {
+            return Err(BigIntError::Negative);
+        }
+
+        if value.significant_bits() as usize > MAX_BIG_INT_BYTES * 8 {
+            return Err(BigIntError::TooLarge);
+        }
+
+        Ok(BigInt(value))
+    }
+
+    /// Decodes value from its canonical big-endian encoding.
+    pub fn decode(bytes: &[u8]) -> Result<BigInt, BigIntError> {
+        if bytes.len() > MAX_BIG_INT_BYTES {
+            return Err(BigIntError::TooLarge);
+        }
+
+        if bytes.first() == Some(&0) {
+            return Err(BigIntError::NonCanonical);
+        }
+
+        Ok(BigInt(integer_from_bytes(bytes)))
+    }
+
+    /// Returns canonical big-endian encoding of the value.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        integer_to_bytes(&self.0)
+    }
+
+    /// Returns the wrapped integer.
+    pub fn as_integer(&self) -> &Integer {
+        &self.0
+    }
+
+    /// Unwraps the integer.
+    pub fn into_integer(self) -> Integer {
+        self.0
+    }
+}
+
+impl From<u64> for BigInt {
+    fn from(value: u64) -> BigInt {
+        BigInt(Integer::from(value))
+    }
+}
+
+impl FromStr for BigInt {
+    type Err = BigIntError;
+
+    fn from_str(s: &str) -> Result<BigInt, BigIntError> {
+        // Only plain decimal digits are accepted, without signs, spaces or underscores
+        if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(BigIntError::Malformed);
+        }
+
+        let value = Integer::from_str_radix(s, 10).map_err(|_| BigIntError::Malformed)?;
+        BigInt::new(value)
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl CryptoHash for BigInt {
+    fn hash(&self) -> Hash {
+        hash(&self.to_bytes())
+    }
+}
+
+impl StorageValue for BigInt {
+    fn into_bytes(self) -> Vec<u8> {
+        self.to_bytes()
+    }
+
+    fn from_bytes(value: Cow<[u8]>) -> BigInt {
+        BigInt(integer_from_bytes(&value))
+    }
+}
+
+impl Serialize for BigInt {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for BigInt {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<BigInt, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
@@ -0,0 +1,154 @@
+use exonum::{
+    blockchain::{Schema as CoreSchema, StoredConfiguration},
+    storage::Snapshot,
+};
+use failure::Error;
+use serde_json;
+
+use backend::VdfBackendKind;
+use SERVICE_NAME;
+
+/// Number of VDF iterations used when the network configuration doesn't specify one.
+pub const DEFAULT_VDF_DIFFICULTY: u64 = 8096 * 16;
+
+/// Number of blocks validators have to agree on the seed of a round by default.
+pub const DEFAULT_COMMIT_TIMEOUT: u64 = 100;
+
+/// Number of blocks validators have to publish VDF results by default.
+pub const DEFAULT_VDF_TIMEOUT: u64 = 1000;
+
+/// Configuration of the service that is stored on-chain in the `services` section
+/// of the actual `StoredConfiguration`.
+///
+/// It is set in the genesis config and can be changed via `exonum-configuration` proposals,
+/// so every validator switches to the new values at the same height.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RngConfig {
+    /// Number of sequential iterations of the VDF.
+    pub vdf_difficulty: u64,
+
+    /// VDF backend used to calculate and verify VDF results.
+    #[serde(default)]
+    pub vdf_backend: VdfBackendKind,
+
+    /// Source of the validators' seed contributions.
+    #[serde(default)]
+    pub contribution_source: ContributionSource,
+
+    /// Number of blocks since the start of a round after which the round is aborted
+    /// if the seed is still not agreed on.
+    #[serde(default = "default_commit_timeout")]
+    pub commit_timeout: u64,
+
+    /// Number of blocks since the seed is agreed on after which the round is aborted
+    /// if there's still not enough VDF results.
+    #[serde(default = "default_vdf_timeout")]
+    pub vdf_timeout: u64,
+}
+
+fn default_commit_timeout() -> u64 {
+    DEFAULT_COMMIT_TIMEOUT
+}
+
+fn default_vdf_timeout() -> u64 {
+    DEFAULT_VDF_TIMEOUT
+}
+
+/// Source of the validators' seed contributions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContributionSource {
+    /// Contributions are 32 random bytes from the OS random number generator.
+    Os,
+
+    /// Contributions are validators' signatures of the round number, see `rng::signed_contribution`.
+    ///
+    /// Contributions can be reproduced after a restart and checked against the validator key,
+    /// but Ed25519 signatures are not unique, so this doesn't make them any harder to bias.
+    Signature,
+}
+
+impl ContributionSource {
+    /// Returns numeric identifier of the source, as it's stored in the blockchain.
+    pub fn id(self) -> u8 {
+        match self {
+            ContributionSource::Os => 0,
+            ContributionSource::Signature => 1,
+        }
+    }
+
+    /// Returns source with the given numeric identifier.
+    pub fn from_id(id: u8) -> Option<ContributionSource> {
+        match id {
+            0 => Some(ContributionSource::Os),
+            1 => Some(ContributionSource::Signature),
+            _ => None,
+        }
+    }
+}
+
+impl Default for ContributionSource {
+    fn default() -> ContributionSource {
+        ContributionSource::Os
+    }
+}
+
+impl Default for RngConfig {
+    fn default() -> RngConfig {
+        RngConfig {
+            vdf_difficulty: DEFAULT_VDF_DIFFICULTY,
+            vdf_backend: VdfBackendKind::default(),
+            contribution_source: ContributionSource::default(),
+            commit_timeout: DEFAULT_COMMIT_TIMEOUT,
+            vdf_timeout: DEFAULT_VDF_TIMEOUT,
+        }
+    }
+}
+
+impl RngConfig {
+    /// Reads the service configuration from the `services` section of the network configuration.
+    ///
+    /// Falls back to the defaults if the service section is missing, e.g. if the network
+    /// has been started without the service configuration. A malformed section is an error.
+    pub fn from_stored(stored: &StoredConfiguration) -> Result<RngConfig, Error> {
+        let config = match stored.services.get(SERVICE_NAME) {
+            Some(value) => serde_json::from_value::<RngConfig>(value.clone())
+                .map_err(|e| format_err!("Malformed configuration of {}: {}", SERVICE_NAME, e))?,
+            None => RngConfig::default(),
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Checks that the VDF difficulty and the round timeouts are positive.
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.vdf_difficulty == 0 || self.commit_timeout == 0 || self.vdf_timeout == 0 {
+            bail!("VDF difficulty and round timeouts must be positive");
+        }
+        Ok(())
+    }
+
+    /// Reads the actual service configuration from the core schema.
+    pub fn actual<T: AsRef<Snapshot>>(snapshot: T) -> Result<RngConfig, Error> {
+        RngConfig::from_stored(&CoreSchema::new(snapshot).actual_configuration())
+    }
+
+    /// Reads the latest valid service configuration that has become actual.
+    ///
+    /// Service sections of configuration proposals aren't checked by the configuration
+    /// service, so a malformed one is rejected here and the service keeps running
+    /// with the configuration that was actual before it.
+    pub fn latest_valid<T: AsRef<Snapshot>>(snapshot: T) -> RngConfig {
+        let schema = CoreSchema::new(snapshot);
+        let next_height = schema.block_hashes_by_height().len();
+        let references = schema.configs_actual_from().iter().collect::<Vec<_>>();
+        references
+            .iter()
+            .rev()
+            .filter(|reference| reference.actual_from().0 <= next_height)
+            .filter_map(|reference| schema.configs().get(reference.cfg_hash()))
+            .filter_map(|stored| RngConfig::from_stored(&stored).ok())
+            .next()
+            .unwrap_or_default()
+    }
+}
@@ -1,11 +1,106 @@
 use exonum::blockchain::ExecutionError;
 
-#[derive(Debug, Fail)]
+#[derive(Debug, Clone, Copy, PartialEq, Fail)]
 #[repr(u8)]
 pub enum Error {
     /// Not enough seed commitments.
+    ///
+    /// Can be emitted by `TxRevealSeed` if the commit phase is not over yet.
     #[fail(display = "Not enough seed commitments.")]
     NotEnoughSeedCommitments = 1,
+
+    /// Author of the transaction is not a validator.
+    #[fail(display = "Author of the transaction is not a validator.")]
+    NotAValidator = 2,
+
+    /// Seed of the current round is not agreed on yet.
+    ///
+    /// Can be emitted by `TxPublishVdfResult`.
+    #[fail(display = "Seed of the current round is not agreed on yet.")]
+    NoSeed = 3,
+
+    /// VDF result is calculated from a seed different from the agreed one.
+    ///
+    /// Can be emitted by `TxPublishVdfResult`.
+    #[fail(display = "VDF result is calculated from a seed different from the agreed one.")]
+    SeedMismatch = 4,
+
+    /// VDF result doesn't pass verification.
+    ///
+    /// Can be emitted by `TxPublishVdfResult`.
+    #[fail(display = "VDF result doesn't pass verification.")]
+    InvalidVdf = 5,
+
+    /// Validator has already made a submission of this kind in the current round.
+    #[fail(display = "Validator has already made a submission of this kind in the current round.")]
+    DuplicateSubmission = 6,
+
+    /// Transaction is made for a round other than the current one.
+    ///
+    /// Can be emitted by `TxPublishSeedCommitment` or `TxRevealSeed`.
+    #[fail(display = "Transaction is made for a round other than the current one.")]
+    WrongRound = 7,
+
+    /// Value is not a canonically encoded big integer, or it is too large.
+    ///
+    /// Can be emitted by `TxRevealSeed` or `TxPublishVdfResult`.
+    #[fail(display = "Value is not a canonically encoded big integer.")]
+    MalformedBigInt = 8,
+
+    /// Commit phase of the current round is already over.
+    ///
+    /// Can be emitted by `TxPublishSeedCommitment`.
+    #[fail(display = "Commit phase of the current round is already over.")]
+    CommitPhaseOver = 9,
+
+    /// Validator hasn't committed to a seed contribution in the current round.
+    ///
+    /// Can be emitted by `TxRevealSeed`.
+    #[fail(display = "Validator hasn't committed to a seed contribution in the current round.")]
+    NotCommitted = 10,
+
+    /// Revealed contribution doesn't match the commitment.
+    ///
+    /// Can be emitted by `TxRevealSeed`.
+    #[fail(display = "Revealed contribution doesn't match the commitment.")]
+    RevealMismatch = 11,
+
+    /// Seed of the current round is already agreed on.
+    ///
+    /// Can be emitted by `TxRevealSeed`.
+    #[fail(display = "Seed of the current round is already agreed on.")]
+    SeedAlreadyAgreed = 12,
+
+    /// Revealed contribution isn't a signature of the round by the validator,
+    /// while the configuration requires signed contributions.
+    ///
+    /// Can be emitted by `TxRevealSeed`.
+    #[fail(display = "Revealed contribution isn't a signature of the round by the validator.")]
+    InvalidContributionSignature = 13,
+
+    /// Requested round is already over.
+    ///
+    /// Can be emitted by `TxRequestRandomness`.
+    #[fail(display = "Requested round is already over.")]
+    RoundIsOver = 14,
+
+    /// Requester already has `MAX_PENDING_REQUESTS` pending requests.
+    ///
+    /// Can be emitted by `TxRequestRandomness`.
+    #[fail(display = "Requester has too many pending requests.")]
+    TooManyRequests = 15,
+
+    /// Requested round is more than `MAX_REQUEST_HORIZON` rounds after the current one.
+    ///
+    /// Can be emitted by `TxRequestRandomness`.
+    #[fail(display = "Requested round is too far in the future.")]
+    RoundTooFar = 16,
+
+    /// Requested round already has `MAX_REQUESTS_PER_ROUND` pending requests.
+    ///
+    /// Can be emitted by `TxRequestRandomness`.
+    #[fail(display = "Requested round has too many pending requests.")]
+    RoundIsFull = 17,
 }
 
 impl From<Error> for ExecutionError {
@@ -13,4 +108,4 @@ impl From<Error> for ExecutionError {
         let description = format!("{}", value);
         ExecutionError::with_description(value as u8, description)
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,111 @@
+use exonum::{
+    blockchain::{Schema as CoreSchema, TransactionErrorType, TransactionResult},
+    crypto::Hash,
+    helpers::Height,
+    messages::RawMessage,
+    storage::{Fork, MapIndex, Snapshot},
+};
+
+use blockchain::{
+    errors::Error,
+    schema::{Equivocation, RngSchema, ValidatorStats},
+    transactions::ExonumRngTransactions,
+};
+use SERVICE_ID;
+
+/// Records evidence of the conflicting submissions refused in the block at `height`.
+///
+/// Refused transactions can't change the state, so the block is scanned for
+/// the submissions that have failed with `Error::DuplicateSubmission` before it's committed.
+/// Each of them is recorded along with the submission of the same validator
+/// that has been accepted earlier. Returns the recorded evidence.
+pub fn record_equivocations(fork: &mut Fork, height: Height) -> Vec<Equivocation> {
+    let equivocations = {
+        let core_schema = CoreSchema::new(&*fork);
+        let schema = RngSchema::new(&*fork);
+        let transactions = core_schema.transactions();
+
+        refused_transactions(&core_schema, height, &[Error::DuplicateSubmission])
+            .iter()
+            .filter_map(|raw| equivocation(&schema, &transactions, raw, height))
+            .collect::<Vec<_>>()
+    };
+
+    let mut schema = RngSchema::new(fork);
+    for equivocation in &equivocations {
+        schema.equivocations_mut().push(equivocation.clone());
+    }
+
+    equivocations
+}
+
+/// Counts VDF results refused as malformed or invalid in the block at `height`
+/// into the statistics of their authors.
+///
+/// Same as with `record_equivocations`, refused transactions can't do it themselves.
+pub fn record_invalid_vdf_results(fork: &mut Fork, height: Height) {
+    let authors = {
+        let core_schema = CoreSchema::new(&*fork);
+        refused_transactions(&core_schema, height, &[Error::MalformedBigInt, Error::InvalidVdf])
+            .into_iter()
+            .filter_map(|raw| match ExonumRngTransactions::tx_from_raw(raw).ok()? {
+                ExonumRngTransactions::TxPublishVdfResult(tx) => Some(*tx.pub_key()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let mut schema = RngSchema::new(fork);
+    let invalid = ValidatorStats::new(0, 0, 0, 1, 0, 0, 0);
+    for pub_key in authors {
+        schema.add_validator_stats(&pub_key, &invalid);
+    }
+}
+
+/// Returns transactions of the service in the block at `height` that have failed
+/// with one of the `errors`.
+fn refused_transactions<T: AsRef<Snapshot>>(
+    core_schema: &CoreSchema<T>,
+    height: Height,
+    errors: &[Error],
+) -> Vec<RawMessage> {
+    let transactions = core_schema.transactions();
+    let results = core_schema.transaction_results();
+    let codes = errors
+        .iter()
+        .map(|error| TransactionErrorType::Code(*error as u8))
+        .collect::<Vec<_>>();
+
+    core_schema
+        .block_transactions(height)
+        .iter()
+        .filter(|tx_hash| match results.get(tx_hash) {
+            Some(TransactionResult(Err(ref error))) => codes.contains(&error.error_type()),
+            _ => false,
+        })
+        .filter_map(|tx_hash| transactions.get(&tx_hash))
+        .filter(|raw| raw.service_id() == SERVICE_ID)
+        .collect()
+}
+
+/// Returns evidence if the refused `raw` transaction conflicts with an accepted one.
+fn equivocation<T: AsRef<Snapshot>>(
+    schema: &RngSchema<T>,
+    transactions: &MapIndex<&Snapshot, Hash, RawMessage>,
+    raw: &RawMessage,
+    height: Height,
+) -> Option<Equivocation> {
+    // Reveals are refused as duplicates too, but they can't conflict
+    // since they have to match the commitment
+    let (pub_key, round, key) = match ExonumRngTransactions::tx_from_raw(raw.clone()).ok()? {
+        ExonumRngTransactions::TxPublishSeedCommitment(tx) => (*tx.pub_key(), tx.round(), tx.submission_key()),
+        ExonumRngTransactions::TxPublishVdfResult(tx) => {
+            (*tx.pub_key(), schema.round_of_seed(tx.seed())?, tx.submission_key())
+        }
+        _ => return None,
+    };
+
+    let first = transactions.get(&schema.submissions().get(&key)?)?;
+    let (first, second): (&[u8], &[u8]) = ((*first).as_ref(), (**raw).as_ref());
+    Some(Equivocation::new(&pub_key, round, first, second, height))
+}
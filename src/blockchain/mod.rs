@@ -1,15 +1,16 @@
+pub mod bigint;
+pub mod config;
 pub mod schema;
 pub mod errors;
+pub mod evidence;
+pub mod proof;
 pub mod transactions;
 
-use exonum::crypto::{hash, Hash};
-
-pub trait ToHash {
-    fn to_hash(&self) -> Hash;
+/// Returns minimal number of seed commitments, reveals or VDF results that finalizes a round phase
+/// for the given number of validators.
+///
+/// `quorum = 2 * f + 1`, where `f = (n - 1) / 3` is the maximal number of byzantine validators.
+pub fn quorum(num_validators: usize) -> usize {
+    let max_byzantine_nodes = num_validators.saturating_sub(1) / 3;
+    2 * max_byzantine_nodes + 1
 }
-
-impl<T> ToHash for T where T: AsRef<str>, {
-    fn to_hash(&self) -> Hash {
-        hash(self.as_ref().as_bytes())
-    }
-}
\ No newline at end of file
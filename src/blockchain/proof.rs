@@ -0,0 +1,132 @@
+use exonum::{
+    blockchain::{Blockchain, BlockProof},
+    crypto::{CryptoHash, Hash, PublicKey},
+    messages::Message,
+    storage::{ListProof, MapProof},
+};
+
+use std::collections::BTreeSet;
+
+use blockchain::schema::{RoundInfo, ROUNDS_TABLE_INDEX, ROUND_TABLE_INDEX};
+use SERVICE_ID;
+
+/// Proof of a finalized round that is tied to a block header and its precommits.
+///
+/// Can be checked by a light client that only knows consensus keys of the validators.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RoundProof {
+    /// Latest committed block and precommits of the validators for it.
+    pub block_info: BlockProof,
+
+    /// Proof of the `round` and `rounds` tables in the state hash of the block.
+    pub to_tables: MapProof<Hash, Hash>,
+
+    /// Length of the rounds history at the moment of the block.
+    pub rounds_len: u64,
+
+    /// Proof of the round in the rounds history.
+    pub to_round: ListProof<RoundInfo>,
+}
+
+/// Errors that can occur while checking a `RoundProof`.
+#[derive(Debug, Fail, PartialEq)]
+pub enum ProofError {
+    /// Precommit is signed by an unknown validator.
+    #[fail(display = "Precommit is signed by an unknown validator.")]
+    UnknownValidator,
+
+    /// Precommit is for a different block.
+    #[fail(display = "Precommit is for a different block.")]
+    PrecommitMismatch,
+
+    /// Precommit signature is invalid.
+    #[fail(display = "Precommit signature is invalid.")]
+    InvalidPrecommitSignature,
+
+    /// Block isn't signed by the majority of validators.
+    #[fail(display = "Block isn't signed by the majority of validators.")]
+    NotEnoughPrecommits,
+
+    /// Proof of the service tables doesn't match the block state hash.
+    #[fail(display = "Proof of the service tables doesn't match the block state hash.")]
+    InvalidTablesProof,
+
+    /// Proof of the round doesn't match the rounds history.
+    #[fail(display = "Proof of the round doesn't match the rounds history.")]
+    InvalidRoundProof,
+
+    /// Requested round isn't contained in the proof.
+    #[fail(display = "Requested round isn't contained in the proof.")]
+    RoundNotFound,
+}
+
+/// Checks proof of the `round` against consensus keys of the validators,
+/// ordered by their validator ids.
+///
+/// Returns the proven round on success.
+pub fn verify_round_proof(
+    proof: &RoundProof,
+    validator_keys: &[PublicKey],
+    round: u64,
+) -> Result<RoundInfo, ProofError> {
+    let block = &proof.block_info.block;
+    let block_hash = block.hash();
+
+    // Block should be signed by +2/3 of the validators.
+    let mut signers = BTreeSet::new();
+    for precommit in &proof.block_info.precommits {
+        let validator_id = precommit.validator().0 as usize;
+        let key = validator_keys.get(validator_id).ok_or(ProofError::UnknownValidator)?;
+
+        if *precommit.block_hash() != block_hash || precommit.height() != block.height() {
+            return Err(ProofError::PrecommitMismatch);
+        }
+
+        if !precommit.verify_signature(key) {
+            return Err(ProofError::InvalidPrecommitSignature);
+        }
+
+        signers.insert(validator_id);
+    }
+
+    let max_byzantine_nodes = (validator_keys.len().max(1) - 1) / 3;
+    if signers.len() <= 2 * max_byzantine_nodes {
+        return Err(ProofError::NotEnoughPrecommits);
+    }
+
+    // Service tables should be included into the block state hash.
+    let to_tables = proof
+        .to_tables
+        .check()
+        .map_err(|_| ProofError::InvalidTablesProof)?;
+    if to_tables.merkle_root() != *block.state_hash() {
+        return Err(ProofError::InvalidTablesProof);
+    }
+
+    let table_hash = |table_idx: usize| {
+        let key = Blockchain::service_table_unique_key(SERVICE_ID, table_idx);
+        to_tables
+            .entries()
+            .into_iter()
+            .find(|&(k, _)| *k == key)
+            .map(|(_, v)| *v)
+            .ok_or(ProofError::InvalidTablesProof)
+    };
+
+    // Length of the history is stored in the `round` entry.
+    if table_hash(ROUND_TABLE_INDEX)? != proof.rounds_len.hash() {
+        return Err(ProofError::InvalidTablesProof);
+    }
+
+    let rounds_root = table_hash(ROUNDS_TABLE_INDEX)?;
+    let rounds = proof
+        .to_round
+        .validate(rounds_root, proof.rounds_len)
+        .map_err(|_| ProofError::InvalidRoundProof)?;
+
+    rounds
+        .into_iter()
+        .find(|&(idx, info)| idx == round && info.round() == round)
+        .map(|(_, info)| info.clone())
+        .ok_or(ProofError::RoundNotFound)
+}
@@ -1,10 +1,269 @@
 use exonum::{
+    blockchain::Schema as CoreSchema,
     crypto::{Hash, PublicKey},
-    storage::{ProofMapIndex, Snapshot, Fork, Entry },
+    helpers::Height,
+    storage::{ListIndex, ProofListIndex, ProofMapIndex, Snapshot, Fork, Entry },
 };
 
-/// For simplicity, big integers are stored as strings
-pub type BigInt = String;
+use backend::VdfBackendKind;
+use blockchain::{
+    bigint::{BigInt, BigIntError},
+    config::{ContributionSource, RngConfig},
+    quorum,
+};
+
+/// Position of the `round` entry in the service state hash.
+pub const ROUND_TABLE_INDEX: usize = 4;
+
+/// Position of the `rounds` history in the service state hash.
+pub const ROUNDS_TABLE_INDEX: usize = 5;
+
+/// Maximal number of pending randomness requests of a single requester.
+pub const MAX_PENDING_REQUESTS: u64 = 16;
+
+/// Maximal number of randomness requests that can be made for a single round.
+///
+/// Requesters aren't registered, so the limit per requester is bypassed by using new keys;
+/// together with `MAX_REQUEST_HORIZON` it bounds the total number of pending requests.
+pub const MAX_REQUESTS_PER_ROUND: u64 = 256;
+
+/// Maximal number of rounds after the current one that randomness can be requested for.
+pub const MAX_REQUEST_HORIZON: u64 = 1024;
+
+encoding_struct! {
+    /// Seed contribution revealed by a validator.
+    struct SeedReveal {
+        /// Value of the contribution, see `BigInt::to_bytes`.
+        value: &[u8],
+
+        /// Salt the contribution was committed with.
+        salt: &Hash,
+    }
+}
+
+encoding_struct! {
+    /// Seed contribution of a validator that has participated in a round.
+    struct SeedCommitment {
+        /// Public key of the validator.
+        pub_key: &PublicKey,
+
+        /// Commitment `hash(value || salt)` published in the commit phase.
+        commitment: &Hash,
+
+        /// Value of the contribution revealed in the reveal phase, see `BigInt::to_bytes`.
+        value: &[u8],
+
+        /// Salt the contribution was committed with.
+        salt: &Hash,
+    }
+}
+
+encoding_struct! {
+    /// Finalized round of the beacon.
+    struct RoundInfo {
+        /// Number of the round.
+        round: u64,
+
+        /// Combined seed the VDF was calculated from.
+        seed: &Hash,
+
+        /// Revealed seed contributions the combined seed was calculated from.
+        commitments: Vec<SeedCommitment>,
+
+        /// Accepted VDF output, see `BigInt::to_bytes`.
+        vdf_output: &[u8],
+
+        /// Proof of the accepted VDF output.
+        vdf_proof: &[u8],
+
+        /// VDF difficulty used in the round.
+        difficulty: u64,
+
+        /// Identifier of the VDF backend used in the round, see `VdfBackendKind::id`.
+        vdf_backend: u8,
+
+        /// Height of the block in which the round was finalized.
+        height: Height,
+
+        /// Randomness derived from the VDF output, see `rng::calculate_randomness`.
+        randomness: &Hash,
+
+        /// Validators that have published valid VDF results different from the accepted one.
+        disagreeing_validators: Vec<PublicKey>,
+
+        /// `true` if the round is aborted because its deadline has passed.
+        ///
+        /// Failed rounds have no VDF output and randomness, their seed is zero
+        /// if it hasn't been agreed on.
+        failed: bool,
+
+        /// Validators that haven't participated in the last phase the round has reached,
+        /// see `RngSchema::missing_validators`.
+        missing_validators: Vec<PublicKey>,
+
+        /// Service keys of the validator set fixed for the round, see `RngSchema::validators`.
+        ///
+        /// The quorum of the round is calculated from this set, so the round can be checked
+        /// after the validator set has changed.
+        validators: Vec<PublicKey>,
+
+        /// Validators that have published valid VDF results equal to the accepted one,
+        /// the round is finalized once there's a quorum of them. Empty for failed rounds.
+        vdf_submitters: Vec<PublicKey>,
+
+        /// Identifier of the source of the contributions in the round,
+        /// see `ContributionSource::id`.
+        contribution_source: u8,
+    }
+}
+
+encoding_struct! {
+    /// Request of randomness of a future round registered by `TxRequestRandomness`.
+    struct RandomnessRequest {
+        /// Public key of the author of the request.
+        requester: &PublicKey,
+
+        /// Round starting from which the randomness is requested.
+        round: u64,
+
+        /// Application-specific tag of the request.
+        tag: &Hash,
+
+        /// Height at which the request is registered.
+        height: Height,
+
+        /// `true` if the request is fulfilled.
+        fulfilled: bool,
+
+        /// First successfully finalized round not earlier than the requested one,
+        /// `0` if the request isn't fulfilled yet.
+        fulfilled_round: u64,
+
+        /// Randomness of the `fulfilled_round`, zero if the request isn't fulfilled yet.
+        randomness: &Hash,
+    }
+}
+
+encoding_struct! {
+    /// Evidence of a validator that has signed two conflicting submissions in a round:
+    /// seed commitments to the same round or VDF results of the same seed.
+    ///
+    /// Submissions are the complete signed transactions, so anyone can check the evidence
+    /// against the validator's service key.
+    struct Equivocation {
+        /// Public key of the validator.
+        pub_key: &PublicKey,
+
+        /// Round the submissions are made in.
+        round: u64,
+
+        /// Submission that has been accepted.
+        first: &[u8],
+
+        /// Conflicting submission that has been refused.
+        second: &[u8],
+
+        /// Height of the block in which the conflicting submission was refused.
+        height: Height,
+    }
+}
+
+encoding_struct! {
+    /// Participation of a validator in the rounds of the beacon.
+    struct ValidatorStats {
+        /// Number of rounds the validator has published a seed commitment in.
+        rounds_committed: u64,
+
+        /// Number of rounds the validator has revealed its contribution in.
+        rounds_revealed: u64,
+
+        /// Number of valid VDF results published by the validator.
+        valid_vdf_results: u64,
+
+        /// Number of VDF results of the validator that have been refused as malformed or invalid.
+        invalid_vdf_results: u64,
+
+        /// Number of rounds the validator has failed to make a submission the round
+        /// has been waiting for: a commitment while the commit phase was open,
+        /// or a reveal or a VDF result after committing to a round that has failed.
+        rounds_missed: u64,
+
+        /// Total number of blocks between the start of a phase and the commitment or
+        /// the valid VDF result of the validator published in it.
+        total_latency: u64,
+
+        /// Number of rounds the validator hasn't participated in the last phase of,
+        /// see `RoundInfo::missing_validators`, because the phase had been closed
+        /// by the quorum of the others before the submission of the validator.
+        rounds_locked_out: u64,
+    }
+}
+
+impl ValidatorStats {
+    /// Returns average number of blocks between the start of a phase and the commitment
+    /// or the valid VDF result of the validator, or `None` if there's none of them.
+    pub fn average_latency(&self) -> Option<f64> {
+        let submissions = self.rounds_committed() + self.valid_vdf_results();
+        if submissions == 0 {
+            return None;
+        }
+
+        Some(self.total_latency() as f64 / submissions as f64)
+    }
+
+    /// Returns sum of the statistics.
+    pub fn add(&self, other: &ValidatorStats) -> ValidatorStats {
+        ValidatorStats::new(
+            self.rounds_committed() + other.rounds_committed(),
+            self.rounds_revealed() + other.rounds_revealed(),
+            self.valid_vdf_results() + other.valid_vdf_results(),
+            self.invalid_vdf_results() + other.invalid_vdf_results(),
+            self.rounds_missed() + other.rounds_missed(),
+            self.total_latency() + other.total_latency(),
+            self.rounds_locked_out() + other.rounds_locked_out(),
+        )
+    }
+}
+
+impl RandomnessRequest {
+    /// Returns the request fulfilled with randomness of the `round`.
+    pub fn fulfill(&self, round: u64, randomness: &Hash) -> RandomnessRequest {
+        RandomnessRequest::new(
+            self.requester(),
+            self.round(),
+            self.tag(),
+            self.height(),
+            true,
+            round,
+            randomness,
+        )
+    }
+}
+
+impl SeedReveal {
+    /// Returns value of the contribution.
+    pub fn contribution(&self) -> BigInt {
+        BigInt::decode(self.value()).expect("Revealed contributions are validated")
+    }
+}
+
+impl SeedCommitment {
+    /// Returns value of the contribution.
+    ///
+    /// Fails only if the round is received from an untrusted source and is malformed.
+    pub fn contribution(&self) -> Result<BigInt, BigIntError> {
+        BigInt::decode(self.value())
+    }
+}
+
+impl RoundInfo {
+    /// Returns accepted VDF output.
+    ///
+    /// Fails only if the round is received from an untrusted source and is malformed.
+    pub fn output(&self) -> Result<BigInt, BigIntError> {
+        BigInt::decode(self.vdf_output())
+    }
+}
 
 #[derive(Debug)]
 pub struct RngSchema<T> {
@@ -22,14 +281,22 @@ impl<T> RngSchema<T> {
 }
 
 impl<T> RngSchema<T> where T: AsRef<Snapshot> {
-    /// Maps validators to their commitments to a seed value.
+    /// Maps validators to their commitments `hash(value || salt)` to a seed contribution.
     ///
-    /// When majority of seed commitments is collected, validator concludes an agreement on the
-    /// next VDF seed value.
-    pub fn validators_seed_commitments(&self) -> ProofMapIndex<&dyn Snapshot, PublicKey, BigInt> {
+    /// When majority of seed commitments is collected, the commit phase is over
+    /// and validators proceed to reveal their contributions.
+    pub fn validators_seed_commitments(&self) -> ProofMapIndex<&dyn Snapshot, PublicKey, Hash> {
         ProofMapIndex::new("exonum_rng.validators_commitments", self.view.as_ref())
     }
 
+    /// Maps validators to their revealed seed contributions.
+    ///
+    /// When majority of contributions is revealed, validator concludes an agreement on the
+    /// next VDF seed value.
+    pub fn validators_seed_reveals(&self) -> ProofMapIndex<&dyn Snapshot, PublicKey, SeedReveal> {
+        ProofMapIndex::new("exonum_rng.validators_reveals", self.view.as_ref())
+    }
+
     /// Returns count of seed commitments posted by a validators in current round.
     pub fn num_seed_commitments(&self) -> usize {
         let seed_commitments = self.validators_seed_commitments();
@@ -41,6 +308,24 @@ impl<T> RngSchema<T> where T: AsRef<Snapshot> {
         count
     }
 
+    /// Returns count of seed contributions revealed by a validators in current round.
+    pub fn num_seed_reveals(&self) -> usize {
+        self.validators_seed_reveals().values().count()
+    }
+
+    /// Returns `true` if the commit phase of the current round is over.
+    pub fn is_commit_phase_over(&self) -> bool {
+        self.num_seed_commitments() >= self.quorum()
+    }
+
+    /// Returns count of valid VDF results equal to `value` posted by a validators in current round.
+    pub fn num_vdf_results_equal_to(&self, value: &BigInt) -> usize {
+        self.validators_vdf_results()
+            .values()
+            .filter(|result| result == value)
+            .count()
+    }
+
     /// Returns count of valid VDF results posted by a validators in current round.
     pub fn num_vdf_results(&self) -> usize {
         let vdf_results = self.validators_vdf_results();
@@ -54,7 +339,7 @@ impl<T> RngSchema<T> where T: AsRef<Snapshot> {
 
     /// Maps validators to their VDF(seed) results.
     ///
-    /// When  majority of the equal VDF results is collected, validator concludes an agreement on the
+    /// When majority of the equal VDF results is collected, validator concludes an agreement on the
     /// next random number.
     pub fn validators_vdf_results(&self) -> ProofMapIndex<&dyn Snapshot, PublicKey, BigInt> {
         ProofMapIndex::new("exonum_rng.vdf_results", self.view.as_ref())
@@ -70,17 +355,307 @@ impl<T> RngSchema<T> where T: AsRef<Snapshot> {
         Entry::new("exonum_rng.randomness", self.view.as_ref())
     }
 
+    /// Service keys of the validators fixed for the current round when it has started.
+    ///
+    /// It's empty until the first block of the first round is committed.
+    pub fn round_validators(&self) -> ProofListIndex<&dyn Snapshot, PublicKey> {
+        ProofListIndex::new("exonum_rng.round_validators", self.view.as_ref())
+    }
+
+    /// Returns service keys of the validators in the actual configuration.
+    pub fn actual_validators(&self) -> Vec<PublicKey> {
+        CoreSchema::new(self.view.as_ref())
+            .actual_configuration()
+            .validator_keys
+            .iter()
+            .map(|keys| keys.service_key)
+            .collect()
+    }
+
+    /// Returns service keys of the validators of the current round.
+    ///
+    /// The validator set is fixed when the round starts, so a configuration change
+    /// doesn't affect the round, see `is_validator_set_changed`.
+    pub fn validators(&self) -> Vec<PublicKey> {
+        let round_validators = self.round_validators();
+        if round_validators.is_empty() {
+            self.actual_validators()
+        } else {
+            round_validators.iter().collect()
+        }
+    }
+
+    /// Returns `true` if the validator set of the actual configuration differs
+    /// from the one of the current round.
+    pub fn is_validator_set_changed(&self) -> bool {
+        let round_validators = self.round_validators();
+        !round_validators.is_empty() && round_validators.iter().collect::<Vec<_>>() != self.actual_validators()
+    }
+
+    /// Returns minimal number of seed commitments, reveals or VDF results that finalizes
+    /// a round phase for the validator set of the current round, see `blockchain::quorum`.
+    pub fn quorum(&self) -> usize {
+        quorum(self.validators().len())
+    }
+
+    /// Returns `true` if `pub_key` is a service key of a validator of the current round.
+    pub fn is_validator(&self, pub_key: &PublicKey) -> bool {
+        self.validators().contains(pub_key)
+    }
+
+    /// Returns number of the current (not yet finalized) round.
+    pub fn round(&self) -> Entry<&dyn Snapshot, u64> {
+        Entry::new("exonum_rng.round", self.view.as_ref())
+    }
+
+    /// Returns number of the current round, starting from `0`.
+    ///
+    /// Every round is recorded into the history, so it also equals the length of `rounds`.
+    pub fn current_round(&self) -> u64 {
+        self.round().get().unwrap_or(0)
+    }
+
+    /// History of the finalized rounds, indexed by the round number.
+    pub fn rounds(&self) -> ProofListIndex<&dyn Snapshot, RoundInfo> {
+        ProofListIndex::new("exonum_rng.rounds", self.view.as_ref())
+    }
+
+    /// Returns finalized round with the given number.
+    pub fn round_info(&self, round: u64) -> Option<RoundInfo> {
+        self.rounds().get(round)
+    }
+
+    /// Returns VDF difficulty that has been fixed for the current round when its seed was agreed on.
+    pub fn round_difficulty(&self) -> Entry<&dyn Snapshot, u64> {
+        Entry::new("exonum_rng.difficulty", self.view.as_ref())
+    }
+
+    /// Returns identifier of the VDF backend that has been fixed for the current round
+    /// when its seed was agreed on.
+    pub fn round_backend(&self) -> Entry<&dyn Snapshot, u8> {
+        Entry::new("exonum_rng.backend", self.view.as_ref())
+    }
+
+    /// Returns actual service configuration.
+    ///
+    /// A malformed configuration is never actual for the service, see `RngConfig::latest_valid`.
+    pub fn actual_config(&self) -> RngConfig {
+        RngConfig::latest_valid(self.view.as_ref())
+    }
+
+    /// Returns VDF difficulty that is active for the current round.
+    ///
+    /// Once the seed is agreed on, the round keeps the difficulty it started with,
+    /// even if the configuration changes before VDF results are collected.
+    pub fn vdf_difficulty(&self) -> u64 {
+        self.round_difficulty()
+            .get()
+            .unwrap_or_else(|| self.actual_config().vdf_difficulty)
+    }
+
+    /// Returns VDF backend that is active for the current round.
+    ///
+    /// Same as the difficulty, it's fixed for the round once the seed is agreed on.
+    pub fn vdf_backend(&self) -> VdfBackendKind {
+        self.round_backend()
+            .get()
+            .and_then(VdfBackendKind::from_id)
+            .unwrap_or_else(|| self.actual_config().vdf_backend)
+    }
+
+    /// Maps hashes of `TxRequestRandomness` transactions to the registered requests.
+    pub fn requests(&self) -> ProofMapIndex<&dyn Snapshot, Hash, RandomnessRequest> {
+        ProofMapIndex::new("exonum_rng.requests", self.view.as_ref())
+    }
+
+    /// Maps hashes of the requests that are not fulfilled yet to the requested rounds.
+    pub fn pending_requests(&self) -> ProofMapIndex<&dyn Snapshot, Hash, u64> {
+        ProofMapIndex::new("exonum_rng.pending_requests", self.view.as_ref())
+    }
+
+    /// Maps keys of the accepted submissions, see `TxPublishSeedCommitment::submission_key`
+    /// and `TxPublishVdfResult::submission_key`, to the hashes of their transactions.
+    pub fn submissions(&self) -> ProofMapIndex<&dyn Snapshot, Hash, Hash> {
+        ProofMapIndex::new("exonum_rng.submissions", self.view.as_ref())
+    }
+
+    /// Evidence of the validators that have signed conflicting submissions.
+    pub fn equivocations(&self) -> ProofListIndex<&dyn Snapshot, Equivocation> {
+        ProofListIndex::new("exonum_rng.equivocations", self.view.as_ref())
+    }
+
+    /// Maps service keys of the validators to their participation in the rounds.
+    pub fn validator_stats(&self) -> ProofMapIndex<&dyn Snapshot, PublicKey, ValidatorStats> {
+        ProofMapIndex::new("exonum_rng.validator_stats", self.view.as_ref())
+    }
+
+    /// Returns number of the round whose seed is `seed`, if it's the current round
+    /// or the latest recorded one.
+    pub fn round_of_seed(&self, seed: &Hash) -> Option<u64> {
+        if self.last_seed().get().as_ref() == Some(seed) {
+            return Some(self.current_round());
+        }
+
+        self.rounds()
+            .last()
+            .filter(|info| info.seed() == seed)
+            .map(|info| info.round())
+    }
+
+    /// Returns height at which the current round has started.
+    pub fn round_start(&self) -> Entry<&dyn Snapshot, u64> {
+        Entry::new("exonum_rng.round_start", self.view.as_ref())
+    }
+
+    /// Returns hashes of the pending requests that are fulfilled once the `round` is finalized.
+    ///
+    /// Requests of an aborted round are carried over to the next one, so the requests
+    /// fulfilled by a round are read without scanning the others. The index duplicates
+    /// `pending_requests`, so it's not a part of the state hash.
+    pub fn pending_requests_of_round(&self, round: u64) -> ListIndex<&dyn Snapshot, Hash> {
+        ListIndex::new_in_family("exonum_rng.pending_requests_by_round", &round, self.view.as_ref())
+    }
+
+    /// Maps requesters to the number of their pending requests, see `MAX_PENDING_REQUESTS`.
+    pub fn pending_requests_count(&self) -> ProofMapIndex<&dyn Snapshot, PublicKey, u64> {
+        ProofMapIndex::new("exonum_rng.pending_requests_count", self.view.as_ref())
+    }
+
+    /// Returns height at which the seed of the current round has been agreed on.
+    pub fn seed_height(&self) -> Entry<&dyn Snapshot, u64> {
+        Entry::new("exonum_rng.seed_height", self.view.as_ref())
+    }
+
+    /// Returns commit timeout that has been fixed for the current round when it started.
+    pub fn round_commit_timeout(&self) -> Entry<&dyn Snapshot, u64> {
+        Entry::new("exonum_rng.commit_timeout", self.view.as_ref())
+    }
+
+    /// Returns VDF timeout that has been fixed for the current round when it started.
+    pub fn round_vdf_timeout(&self) -> Entry<&dyn Snapshot, u64> {
+        Entry::new("exonum_rng.vdf_timeout", self.view.as_ref())
+    }
+
+    /// Returns identifier of the contribution source that has been fixed for the current round
+    /// when it started.
+    pub fn round_contribution_source(&self) -> Entry<&dyn Snapshot, u8> {
+        Entry::new("exonum_rng.contribution_source", self.view.as_ref())
+    }
+
+    /// Returns commit timeout that is active for the current round.
+    ///
+    /// The round keeps the timeouts it started with, so a configuration change
+    /// doesn't move the deadline of the open round.
+    pub fn commit_timeout(&self) -> u64 {
+        self.round_commit_timeout()
+            .get()
+            .unwrap_or_else(|| self.actual_config().commit_timeout)
+    }
+
+    /// Returns VDF timeout that is active for the current round.
+    ///
+    /// Same as the commit timeout, it's fixed for the round once the round starts.
+    pub fn vdf_timeout(&self) -> u64 {
+        self.round_vdf_timeout()
+            .get()
+            .unwrap_or_else(|| self.actual_config().vdf_timeout)
+    }
+
+    /// Returns contribution source that is active for the current round.
+    ///
+    /// Contributions of the round are made before they are revealed, so they are checked
+    /// against the source the round has started with rather than the actual one.
+    pub fn contribution_source(&self) -> ContributionSource {
+        self.round_contribution_source()
+            .get()
+            .and_then(ContributionSource::from_id)
+            .unwrap_or_else(|| self.actual_config().contribution_source)
+    }
+
+    /// Returns height starting from which the current phase of the round is expired.
+    ///
+    /// Validators have `commit_timeout` blocks since the start of the round to agree on
+    /// the seed, and `vdf_timeout` blocks since the seed is agreed on to publish VDF results.
+    pub fn phase_deadline(&self) -> Height {
+        let deadline = match self.seed_height().get() {
+            Some(height) => height.saturating_add(self.vdf_timeout()),
+            None => self.round_start().get().unwrap_or(0).saturating_add(self.commit_timeout()),
+        };
+
+        Height(deadline)
+    }
+
+    /// Returns service keys of the validators that haven't participated in the current phase
+    /// of the round: haven't published a commitment in the commit phase, haven't revealed
+    /// their contribution in the reveal phase or haven't published a VDF result after
+    /// the seed is agreed on.
+    pub fn missing_validators(&self) -> Vec<PublicKey> {
+        let participants = if self.last_seed().exists() {
+            self.validators_vdf_results().keys().collect::<Vec<_>>()
+        } else if self.is_commit_phase_over() {
+            self.validators_seed_reveals().keys().collect()
+        } else {
+            self.validators_seed_commitments().keys().collect()
+        };
+
+        self.validators()
+            .into_iter()
+            .filter(|pub_key| !participants.contains(pub_key))
+            .collect()
+    }
+
+    /// Returns commitments of the validators that have revealed their contributions.
+    fn revealed_commitments(&self) -> Vec<SeedCommitment> {
+        let commitments = self.validators_seed_commitments();
+        self.validators_seed_reveals()
+            .iter()
+            .filter_map(|(pub_key, reveal)| {
+                commitments.get(&pub_key).map(|commitment| {
+                    SeedCommitment::new(&pub_key, &commitment, reveal.value(), reveal.salt())
+                })
+            })
+            .collect()
+    }
+
+    /// Tables are referenced by their position in this list, see `ROUND_TABLE_INDEX`
+    /// and `ROUNDS_TABLE_INDEX`, so new tables should only be appended to the end.
     pub fn state_hash(&self) -> Vec<Hash> {
-        vec![self.validators_seed_commitments().merkle_root(), self.last_seed().hash(), self.last_randomness().hash()]
+        vec![
+            self.validators_seed_commitments().merkle_root(),
+            self.last_seed().hash(),
+            self.last_randomness().hash(),
+            self.round_difficulty().hash(),
+            self.round().hash(),
+            self.rounds().merkle_root(),
+            self.validators_seed_reveals().merkle_root(),
+            self.round_backend().hash(),
+            self.round_start().hash(),
+            self.seed_height().hash(),
+            self.requests().merkle_root(),
+            self.pending_requests().merkle_root(),
+            self.submissions().merkle_root(),
+            self.equivocations().merkle_root(),
+            self.validator_stats().merkle_root(),
+            self.round_validators().merkle_root(),
+            self.round_commit_timeout().hash(),
+            self.round_vdf_timeout().hash(),
+            self.pending_requests_count().merkle_root(),
+            self.round_contribution_source().hash(),
+        ]
     }
 }
 
 impl<'a> RngSchema<&'a mut Fork> {
     /// Mutable reference to the `validators_seed_commitments` index.
-    pub fn validators_seed_commitments_mut(&mut self) -> ProofMapIndex<&mut Fork, PublicKey, BigInt> {
+    pub fn validators_seed_commitments_mut(&mut self) -> ProofMapIndex<&mut Fork, PublicKey, Hash> {
         ProofMapIndex::new("exonum_rng.validators_commitments", self.view)
     }
 
+    /// Mutable reference to the `validators_seed_reveals` index.
+    pub fn validators_seed_reveals_mut(&mut self) -> ProofMapIndex<&mut Fork, PublicKey, SeedReveal> {
+        ProofMapIndex::new("exonum_rng.validators_reveals", self.view)
+    }
+
     /// Mutable reference to the `validators_vdf_results` index.
     pub fn validators_vdf_results_mut(&mut self) -> ProofMapIndex<&mut Fork, PublicKey, BigInt> {
         ProofMapIndex::new("exonum_rng.vdf_results", self.view)
@@ -95,4 +670,284 @@ impl<'a> RngSchema<&'a mut Fork> {
     pub fn last_seed_mut(&mut self) -> Entry<&mut Fork, Hash> {
         Entry::new("exonum_rng.seed", self.view)
     }
-}
\ No newline at end of file
+
+    /// Mutable reference to the `round` index.
+    pub fn round_mut(&mut self) -> Entry<&mut Fork, u64> {
+        Entry::new("exonum_rng.round", self.view)
+    }
+
+    /// Mutable reference to the `rounds` index.
+    pub fn rounds_mut(&mut self) -> ProofListIndex<&mut Fork, RoundInfo> {
+        ProofListIndex::new("exonum_rng.rounds", self.view)
+    }
+
+    /// Mutable reference to the `round_difficulty` index.
+    pub fn round_difficulty_mut(&mut self) -> Entry<&mut Fork, u64> {
+        Entry::new("exonum_rng.difficulty", self.view)
+    }
+
+    /// Mutable reference to the `round_backend` index.
+    pub fn round_backend_mut(&mut self) -> Entry<&mut Fork, u8> {
+        Entry::new("exonum_rng.backend", self.view)
+    }
+
+    /// Mutable reference to the `round_start` index.
+    pub fn round_start_mut(&mut self) -> Entry<&mut Fork, u64> {
+        Entry::new("exonum_rng.round_start", self.view)
+    }
+
+    /// Mutable reference to the `seed_height` index.
+    pub fn seed_height_mut(&mut self) -> Entry<&mut Fork, u64> {
+        Entry::new("exonum_rng.seed_height", self.view)
+    }
+
+    /// Mutable reference to the `requests` index.
+    pub fn requests_mut(&mut self) -> ProofMapIndex<&mut Fork, Hash, RandomnessRequest> {
+        ProofMapIndex::new("exonum_rng.requests", self.view)
+    }
+
+    /// Mutable reference to the `pending_requests` index.
+    pub fn pending_requests_mut(&mut self) -> ProofMapIndex<&mut Fork, Hash, u64> {
+        ProofMapIndex::new("exonum_rng.pending_requests", self.view)
+    }
+
+    /// Mutable reference to the `pending_requests_of_round` index.
+    pub fn pending_requests_of_round_mut(&mut self, round: u64) -> ListIndex<&mut Fork, Hash> {
+        ListIndex::new_in_family("exonum_rng.pending_requests_by_round", &round, self.view)
+    }
+
+    /// Mutable reference to the `pending_requests_count` index.
+    pub fn pending_requests_count_mut(&mut self) -> ProofMapIndex<&mut Fork, PublicKey, u64> {
+        ProofMapIndex::new("exonum_rng.pending_requests_count", self.view)
+    }
+
+    /// Mutable reference to the `submissions` index.
+    pub fn submissions_mut(&mut self) -> ProofMapIndex<&mut Fork, Hash, Hash> {
+        ProofMapIndex::new("exonum_rng.submissions", self.view)
+    }
+
+    /// Mutable reference to the `equivocations` index.
+    pub fn equivocations_mut(&mut self) -> ProofListIndex<&mut Fork, Equivocation> {
+        ProofListIndex::new("exonum_rng.equivocations", self.view)
+    }
+
+    /// Mutable reference to the `validator_stats` index.
+    pub fn validator_stats_mut(&mut self) -> ProofMapIndex<&mut Fork, PublicKey, ValidatorStats> {
+        ProofMapIndex::new("exonum_rng.validator_stats", self.view)
+    }
+
+    /// Mutable reference to the `round_validators` index.
+    pub fn round_validators_mut(&mut self) -> ProofListIndex<&mut Fork, PublicKey> {
+        ProofListIndex::new("exonum_rng.round_validators", self.view)
+    }
+
+    /// Mutable reference to the `round_commit_timeout` index.
+    pub fn round_commit_timeout_mut(&mut self) -> Entry<&mut Fork, u64> {
+        Entry::new("exonum_rng.commit_timeout", self.view)
+    }
+
+    /// Mutable reference to the `round_vdf_timeout` index.
+    pub fn round_vdf_timeout_mut(&mut self) -> Entry<&mut Fork, u64> {
+        Entry::new("exonum_rng.vdf_timeout", self.view)
+    }
+
+    /// Mutable reference to the `round_contribution_source` index.
+    pub fn round_contribution_source_mut(&mut self) -> Entry<&mut Fork, u8> {
+        Entry::new("exonum_rng.contribution_source", self.view)
+    }
+
+    /// Fixes the contribution source of the actual configuration for the current round.
+    pub fn fix_round_contribution_source(&mut self) {
+        let source = self.actual_config().contribution_source;
+        self.round_contribution_source_mut().set(source.id());
+    }
+
+    /// Fixes the timeouts of the actual configuration for the current round.
+    pub fn fix_round_timeouts(&mut self) {
+        let config = self.actual_config();
+        self.round_commit_timeout_mut().set(config.commit_timeout);
+        self.round_vdf_timeout_mut().set(config.vdf_timeout);
+    }
+
+    /// Fixes the validator set of the actual configuration for the current round.
+    pub fn fix_round_validators(&mut self) {
+        let validators = self.actual_validators();
+        let mut round_validators = self.round_validators_mut();
+        round_validators.clear();
+        round_validators.extend(validators);
+    }
+
+    /// Adds `stats` to the statistics of the validator.
+    pub fn add_validator_stats(&mut self, pub_key: &PublicKey, stats: &ValidatorStats) {
+        let total = match self.validator_stats().get(pub_key) {
+            Some(current) => current.add(stats),
+            None => stats.clone(),
+        };
+        self.validator_stats_mut().put(pub_key, total);
+    }
+
+    /// Registers pending request with the hash `id`, the `round` shouldn't be over.
+    pub fn add_request(&mut self, id: &Hash, request: RandomnessRequest) {
+        let count = self.pending_requests_count().get(request.requester()).unwrap_or(0);
+        self.pending_requests_count_mut().put(request.requester(), count + 1);
+        self.pending_requests_mut().put(id, request.round());
+        self.pending_requests_of_round_mut(request.round()).push(*id);
+        self.requests_mut().put(id, request);
+    }
+
+    /// Fulfills pending requests of the rounds up to the `round` with its randomness.
+    fn fulfill_requests(&mut self, round: u64, randomness: &Hash) {
+        let fulfilled = self.pending_requests_of_round(round).iter().collect::<Vec<_>>();
+        for id in fulfilled {
+            let request = self.requests().get(&id).expect("Pending request is registered");
+            let count = self.pending_requests_count().get(request.requester()).unwrap_or(0);
+            if count > 1 {
+                self.pending_requests_count_mut().put(request.requester(), count - 1);
+            } else {
+                self.pending_requests_count_mut().remove(request.requester());
+            }
+
+            self.requests_mut().put(&id, request.fulfill(round, randomness));
+            self.pending_requests_mut().remove(&id);
+        }
+        self.pending_requests_of_round_mut(round).clear();
+    }
+
+    /// Carries pending requests of the aborted `round` over to the next one.
+    fn carry_over_requests(&mut self, round: u64) {
+        let pending = self.pending_requests_of_round(round).iter().collect::<Vec<_>>();
+        self.pending_requests_of_round_mut(round + 1).extend(pending);
+        self.pending_requests_of_round_mut(round).clear();
+    }
+
+    /// Fixes the seed and VDF parameters of the current round, agreed on at the given `height`.
+    pub fn set_round_seed(&mut self, seed: Hash, height: Height) {
+        let config = self.actual_config();
+        self.last_seed_mut().set(seed);
+        self.seed_height_mut().set(height.0);
+        self.round_difficulty_mut().set(config.vdf_difficulty);
+        self.round_backend_mut().set(config.vdf_backend.id());
+    }
+
+    /// Records current round into the history as finalized and starts the next round.
+    pub fn finalize_round(
+        &mut self,
+        seed: &Hash,
+        vdf_output: &BigInt,
+        vdf_proof: &[u8],
+        height: Height,
+        randomness: &Hash,
+    ) -> RoundInfo {
+        let disagreeing_validators = self.validators_vdf_results()
+            .iter()
+            .filter(|&(_, ref value)| value != vdf_output)
+            .map(|(pub_key, _)| pub_key)
+            .collect::<Vec<_>>();
+        let vdf_submitters = self.validators_vdf_results()
+            .iter()
+            .filter(|&(_, ref value)| value == vdf_output)
+            .map(|(pub_key, _)| pub_key)
+            .collect::<Vec<_>>();
+
+        let info = RoundInfo::new(
+            self.current_round(),
+            seed,
+            self.revealed_commitments(),
+            &vdf_output.to_bytes(),
+            vdf_proof,
+            self.vdf_difficulty(),
+            self.vdf_backend().id(),
+            height,
+            randomness,
+            disagreeing_validators,
+            false,
+            self.missing_validators(),
+            self.validators(),
+            vdf_submitters,
+            self.contribution_source().id(),
+        );
+        self.last_randomness_mut().set(*randomness);
+        self.fulfill_requests(info.round(), randomness);
+        self.penalize_missing_validators(&info);
+        self.start_next_round(info)
+    }
+
+    /// Records current round into the history as failed and starts the next round.
+    pub fn abort_round(&mut self, height: Height) -> RoundInfo {
+        let info = self.failed_round_info(height);
+        self.penalize_missing_validators(&info);
+        self.carry_over_requests(info.round());
+        self.start_next_round(info)
+    }
+
+    /// Records current round into the history as failed and starts the next round
+    /// without penalizing the missing validators.
+    ///
+    /// Used when the round is aborted because of a configuration change rather than
+    /// the validators, e.g. when the validator set is changed.
+    pub fn restart_round(&mut self, height: Height) -> RoundInfo {
+        let info = self.failed_round_info(height);
+        self.carry_over_requests(info.round());
+        self.start_next_round(info)
+    }
+
+    /// Returns record of the current round as a failed one.
+    fn failed_round_info(&self, height: Height) -> RoundInfo {
+        let seed = self.last_seed().get().unwrap_or_else(Hash::zero);
+        RoundInfo::new(
+            self.current_round(),
+            &seed,
+            self.revealed_commitments(),
+            &[],
+            &[],
+            self.vdf_difficulty(),
+            self.vdf_backend().id(),
+            height,
+            &Hash::zero(),
+            Vec::new(),
+            true,
+            self.missing_validators(),
+            self.validators(),
+            Vec::new(),
+            self.contribution_source().id(),
+        )
+    }
+
+    /// Adds missed or locked out rounds to the statistics of the validators missing from
+    /// the round `info`.
+    fn penalize_missing_validators(&mut self, info: &RoundInfo) {
+        // Once the commit phase is closed by the quorum, validators that haven't committed
+        // can't participate anymore. Committed validators are waited for until the round
+        // is finalized by the quorum of VDF results or fails.
+        let is_commit_phase_over = self.is_commit_phase_over();
+        let committed = self.validators_seed_commitments().keys().collect::<Vec<_>>();
+        let missed = ValidatorStats::new(0, 0, 0, 0, 1, 0, 0);
+        let locked_out = ValidatorStats::new(0, 0, 0, 0, 0, 0, 1);
+        for pub_key in info.missing_validators() {
+            let is_missed = !is_commit_phase_over || (info.failed() && committed.contains(&pub_key));
+            self.add_validator_stats(&pub_key, if is_missed { &missed } else { &locked_out });
+        }
+    }
+
+    /// Pushes `info` into the history, clears leftovers of the round and starts the next one
+    /// at the height the round has ended.
+    fn start_next_round(&mut self, info: RoundInfo) -> RoundInfo {
+        self.rounds_mut().push(info.clone());
+        self.round_mut().set(info.round() + 1);
+        self.round_start_mut().set(info.height().0);
+
+        // Clear leftovers to not mess with next rounds
+        self.last_seed_mut().remove();
+        self.seed_height_mut().remove();
+        self.round_difficulty_mut().remove();
+        self.round_backend_mut().remove();
+        self.validators_vdf_results_mut().clear();
+        self.validators_seed_reveals_mut().clear();
+        self.validators_seed_commitments_mut().clear();
+        self.fix_round_validators();
+        self.fix_round_timeouts();
+        self.fix_round_contribution_source();
+
+        info
+    }
+}
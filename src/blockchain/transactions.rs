@@ -1,19 +1,32 @@
 use exonum::{
     storage::Fork,
-    crypto::Hash,
+    crypto::{hash, Hash},
+    helpers::Height,
     blockchain::{Transaction, ExecutionError, Schema as CoreSchema},
     messages::Message,
 };
 
 use blockchain::{
-    schema::RngSchema,
-    ToHash
+    bigint::BigInt,
+    config::ContributionSource,
+    errors::Error,
+    schema::{
+        RandomnessRequest, RngSchema, SeedReveal, ValidatorStats, MAX_PENDING_REQUESTS,
+        MAX_REQUESTS_PER_ROUND, MAX_REQUEST_HORIZON,
+    },
 };
 
 use exonum::crypto::PublicKey;
+use metrics;
 use SERVICE_ID;
 
-use rng::{calculate_combined_seed, validate_vdf};
+use std::time::Instant;
+
+use backend::{put_u64_be, VdfOutput};
+use rng::{
+    calculate_combined_seed, calculate_randomness, calculate_seed_commitment, validate_vdf,
+    verify_signed_contribution,
+};
 
 transactions! {
     pub ExonumRngTransactions {
@@ -23,8 +36,25 @@ transactions! {
             /// Public key of the author.
             pub_key: &PublicKey,
 
-            /// Value of the seed commitment.
-            value: &str,
+            /// Round the commitment is made for.
+            round: u64,
+
+            /// Commitment `hash(value || salt)` to the seed contribution.
+            commitment: &Hash,
+        }
+
+        struct TxRevealSeed {
+            /// Public key of the author.
+            pub_key: &PublicKey,
+
+            /// Round the contribution is revealed for.
+            round: u64,
+
+            /// Value of the seed contribution, see `BigInt::to_bytes`.
+            value: &[u8],
+
+            /// Salt the contribution was committed with.
+            salt: &Hash,
         }
 
         struct TxPublishVdfResult {
@@ -37,49 +67,164 @@ transactions! {
             /// Value of the VDF function to be verified by others against the seed.
             /// Also serves as an unpredictable random number candidate.
             ///
-            /// Majority of *valid* VDF results is used as current randomness value.
-            value: &str,
+            /// Majority of equal *valid* VDF results determines the randomness of the round,
+            /// see `rng::calculate_randomness`. Encoded with `BigInt::to_bytes`.
+            value: &[u8],
+
+            /// Proof of the VDF value, see `VdfOutput::encoded_proof`.
+            proof: &[u8],
+        }
+
+        struct TxRequestRandomness {
+            /// Public key of the author, can be any key.
+            requester: &PublicKey,
+
+            /// Round starting from which the randomness is requested, can't be earlier than
+            /// the current round or more than `MAX_REQUEST_HORIZON` rounds after it.
+            round: u64,
+
+            /// Application-specific tag of the request.
+            tag: &Hash,
         }
     }
 }
 
+/// Returns key of a submission in `RngSchema::submissions`.
+fn submission_key(domain: &[u8], pub_key: &PublicKey, subject: &[u8]) -> Hash {
+    let mut bytes = domain.to_vec();
+    bytes.extend_from_slice(pub_key.as_ref());
+    bytes.extend_from_slice(subject);
+    hash(&bytes)
+}
+
+impl TxPublishSeedCommitment {
+    /// Returns key of the commitment in `RngSchema::submissions`,
+    /// that is the same for all commitments of the validator to the round.
+    pub fn submission_key(&self) -> Hash {
+        let mut round = Vec::with_capacity(8);
+        put_u64_be(&mut round, self.round());
+        submission_key(b"exonum_rng.commitment", self.pub_key(), &round)
+    }
+}
+
+impl TxPublishVdfResult {
+    /// Returns key of the result in `RngSchema::submissions`,
+    /// that is the same for all results of the validator for the seed.
+    pub fn submission_key(&self) -> Hash {
+        submission_key(b"exonum_rng.vdf_result", self.pub_key(), self.seed().as_ref())
+    }
+}
+
 impl Transaction for TxPublishSeedCommitment {
     fn verify(&self) -> bool {
         self.verify_signature(self.pub_key())
     }
 
     fn execute(&self, fork: &mut Fork) -> Result<(), ExecutionError> {
-        let num_nodes = {
-            let core_schema = CoreSchema::new(&*fork);
-            core_schema.actual_configuration().validator_keys.len()
-        };
+        // Height of the block that is being created now.
+        let height = Height(CoreSchema::new(&*fork).block_hashes_by_height().len());
 
         let mut schema = RngSchema::new(fork);
 
-        schema.validators_seed_commitments_mut().put(&self.pub_key(), self.value().to_owned());
+        if !schema.is_validator(self.pub_key()) {
+            return Err(Error::NotAValidator.into());
+        }
+
+        if self.round() != schema.current_round() {
+            return Err(Error::WrongRound.into());
+        }
+
+        // Second commitment is refused even after the commit phase is over,
+        // so it's recorded as an equivocation, see `evidence::record_equivocations`
+        if schema.validators_seed_commitments().contains(self.pub_key()) {
+            return Err(Error::DuplicateSubmission.into());
+        }
+
+        if schema.is_commit_phase_over() {
+            return Err(Error::CommitPhaseOver.into());
+        }
+
+        schema.validators_seed_commitments_mut().put(&self.pub_key(), *self.commitment());
+        schema.submissions_mut().put(&self.submission_key(), self.hash());
+
+        let latency = height.0.saturating_sub(schema.round_start().get().unwrap_or(0));
+        schema.add_validator_stats(self.pub_key(), &ValidatorStats::new(1, 0, 0, 0, 0, latency, 0));
+
+        Ok(())
+    }
+}
+
+impl Transaction for TxRevealSeed {
+    fn verify(&self) -> bool {
+        self.verify_signature(self.pub_key())
+    }
+
+    fn execute(&self, fork: &mut Fork) -> Result<(), ExecutionError> {
+        // Height of the block that is being created now.
+        let height = Height(CoreSchema::new(&*fork).block_hashes_by_height().len());
+
+        let mut schema = RngSchema::new(fork);
+
+        if !schema.is_validator(self.pub_key()) {
+            return Err(Error::NotAValidator.into());
+        }
+
+        if self.round() != schema.current_round() {
+            return Err(Error::WrongRound.into());
+        }
+
+        // Contributions can't be revealed before the commit phase is over
+        // or after the seed has been agreed on
+        if !schema.is_commit_phase_over() {
+            return Err(Error::NotEnoughSeedCommitments.into());
+        }
+
+        if schema.last_seed().exists() {
+            return Err(Error::SeedAlreadyAgreed.into());
+        }
+
+        // Only contributions of the committed validators are accepted
+        let commitment = match schema.validators_seed_commitments().get(self.pub_key()) {
+            Some(commitment) => commitment,
+            None => return Err(Error::NotCommitted.into()),
+        };
+
+        if schema.validators_seed_reveals().contains(self.pub_key()) {
+            return Err(Error::DuplicateSubmission.into());
+        }
+
+        let value = match BigInt::decode(self.value()) {
+            Ok(value) => value,
+            Err(_) => return Err(Error::MalformedBigInt.into()),
+        };
+
+        if calculate_seed_commitment(&value, self.salt()) != commitment {
+            return Err(Error::RevealMismatch.into());
+        }
+
+        if schema.contribution_source() == ContributionSource::Signature
+            && !verify_signed_contribution(&value, self.round(), self.pub_key())
+        {
+            return Err(Error::InvalidContributionSignature.into());
+        }
 
-        // Check that validator has collected enough seed commitments
+        schema
+            .validators_seed_reveals_mut()
+            .put(self.pub_key(), SeedReveal::new(self.value(), self.salt()));
+        schema.add_validator_stats(self.pub_key(), &ValidatorStats::new(0, 1, 0, 0, 0, 0, 0));
+
+        // Check that validator has collected enough revealed contributions
         // NB: this rule probably could be relaxed
-        let max_byzantine_nodes = (num_nodes - 1) / 3;
-        if schema.num_seed_commitments() > 2 * max_byzantine_nodes {
-            let commitments= {
-                let commitments_idx = schema.validators_seed_commitments();
-                let mut comms = commitments_idx
-                    .values()
-                    .collect::<Vec<_>>();
-
-                // Sort commitments
-                comms.sort_unstable();
-                comms
-            };
-
-            let seed = calculate_combined_seed(&commitments);
+        if schema.num_seed_reveals() >= schema.quorum() {
+            let contributions = schema.validators_seed_reveals()
+                .iter()
+                .map(|(pub_key, reveal)| (pub_key, reveal.contribution()))
+                .collect::<Vec<_>>();
+
+            let seed = calculate_combined_seed(self.round(), &contributions);
             //println!("Calculated combined seed: {}", seed);
 
-            schema.last_seed_mut().set(seed);
-        } else {
-            //println!("Not enough seed commitments: {} <= {}",
-            //         schema.num_seed_commitments(), 2 * max_byzantine_nodes);
+            schema.set_round_seed(seed, height);
         }
 
         Ok(())
@@ -92,45 +237,113 @@ impl Transaction for TxPublishVdfResult {
     }
 
     fn execute(&self, fork: &mut Fork) -> Result<(), ExecutionError> {
-        let num_nodes = {
-            let core_schema = CoreSchema::new(&*fork);
-            core_schema.actual_configuration().validator_keys.len()
-        };
+        // Height of the block that is being created now.
+        let height = Height(CoreSchema::new(&*fork).block_hashes_by_height().len());
 
         let mut schema = RngSchema::new(fork);
 
-        // Ignore VDF result if there is no seed
+        if !schema.is_validator(self.pub_key()) {
+            return Err(Error::NotAValidator.into());
+        }
+
         let current_seed = match schema.last_seed().get() {
             Some(seed) => seed,
-            None => return Ok(())
+            None => return Err(Error::NoSeed.into()),
         };
 
-        // Ignore VDF result with seed different from agreed one
         if *self.seed() != current_seed {
-            return Ok(())
+            return Err(Error::SeedMismatch.into());
         }
 
-        if !validate_vdf(self.seed(), &self.value().to_owned()) {
-            return Ok(())
+        if schema.validators_vdf_results().contains(self.pub_key()) {
+            return Err(Error::DuplicateSubmission.into());
         }
 
-        schema.validators_vdf_results_mut().put(&self.pub_key(), self.value().to_owned());
+        let value = match BigInt::decode(self.value()) {
+            Ok(value) => value,
+            Err(_) => return Err(Error::MalformedBigInt.into()),
+        };
+
+        let output = match VdfOutput::decode(self.value(), self.proof()) {
+            Some(output) => output,
+            None => return Err(Error::MalformedBigInt.into()),
+        };
 
-        // Check that validator has collected enough VDF results
-        // NB: this rule probably could be relaxed
-        let max_byzantine_nodes = (num_nodes - 1) / 3;
-        if schema.num_vdf_results() > 2 * max_byzantine_nodes {
-            let vdf_result_candidate = self.value();
-            schema.last_randomness_mut().set(vdf_result_candidate.to_hash());
+        let backend = schema.vdf_backend().backend();
+        let started = Instant::now();
+        let is_valid = validate_vdf(&*backend, self.seed(), &output, schema.vdf_difficulty());
+        metrics::observe_vdf_verification(started.elapsed());
+        if !is_valid {
+            return Err(Error::InvalidVdf.into());
+        }
+
+        schema.validators_vdf_results_mut().put(&self.pub_key(), value.clone());
+        schema.submissions_mut().put(&self.submission_key(), self.hash());
+
+        let latency = height.0.saturating_sub(schema.seed_height().get().unwrap_or(0));
+        schema.add_validator_stats(self.pub_key(), &ValidatorStats::new(0, 0, 1, 0, 0, latency, 0));
+
+        // Check that validator has collected enough VDF results equal to this one.
+        // Valid results of a deterministic VDF can't differ, but if they do,
+        // the round is finalized only by the majority of agreeing results
+        // and authors of the others are recorded.
+        if schema.num_vdf_results_equal_to(&value) >= schema.quorum() {
+            let randomness = calculate_randomness(schema.current_round(), &current_seed, &value);
+            let info = schema.finalize_round(
+                &current_seed,
+                &value,
+                self.proof(),
+                height,
+                &randomness,
+            );
+
+            println!("[SUCCESS] Randomness value of round {}: {}", info.round(), randomness.to_hex());
+        }
+
+        Ok(())
+    }
+}
+
+impl Transaction for TxRequestRandomness {
+    fn verify(&self) -> bool {
+        self.verify_signature(self.requester())
+    }
+
+    fn execute(&self, fork: &mut Fork) -> Result<(), ExecutionError> {
+        // Height of the block that is being created now.
+        let height = Height(CoreSchema::new(&*fork).block_hashes_by_height().len());
+
+        let mut schema = RngSchema::new(fork);
+
+        if self.round() < schema.current_round() {
+            return Err(Error::RoundIsOver.into());
+        }
+
+        if self.round() - schema.current_round() > MAX_REQUEST_HORIZON {
+            return Err(Error::RoundTooFar.into());
+        }
 
-            // Clear leftovers to not mess with next rounds
-            schema.last_seed_mut().remove();
-            schema.validators_vdf_results_mut().clear();
-            schema.validators_seed_commitments_mut().clear();
+        if schema.pending_requests_of_round(self.round()).len() >= MAX_REQUESTS_PER_ROUND {
+            return Err(Error::RoundIsFull.into());
+        }
 
-            println!("[SUCCESS] Current randomness value: {}", vdf_result_candidate.to_hash().to_hex());
+        let pending = schema.pending_requests_count().get(self.requester()).unwrap_or(0);
+        if pending >= MAX_PENDING_REQUESTS {
+            return Err(Error::TooManyRequests.into());
         }
 
+        // Request is fulfilled as soon as the round or any later one is finalized
+        let request = RandomnessRequest::new(
+            self.requester(),
+            self.round(),
+            self.tag(),
+            height,
+            false,
+            0,
+            &Hash::zero(),
+        );
+        schema.add_request(&self.hash(), request);
+
         Ok(())
     }
-}
\ No newline at end of file
+}
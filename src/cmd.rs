@@ -0,0 +1,235 @@
+use exonum::{
+    helpers::fabric::{keys, Argument, CommandExtension, Context},
+    node::NodeConfig,
+};
+use failure::Error;
+use serde::de::DeserializeOwned;
+use serde_json;
+use toml::Value;
+
+use std::collections::BTreeMap;
+
+use blockchain::config::{ContributionSource, RngConfig};
+use rng::{CheckpointFile, ContributionFile};
+use SERVICE_NAME;
+
+const VDF_DIFFICULTY: &str = "RNG_VDF_DIFFICULTY";
+const VDF_BACKEND: &str = "RNG_VDF_BACKEND";
+const CONTRIBUTION_SOURCE: &str = "RNG_CONTRIBUTION_SOURCE";
+const COMMIT_TIMEOUT: &str = "RNG_COMMIT_TIMEOUT";
+const VDF_TIMEOUT: &str = "RNG_VDF_TIMEOUT";
+const VDF_CHECKPOINT: &str = "RNG_VDF_CHECKPOINT";
+const CONTRIBUTION_FILE: &str = "RNG_CONTRIBUTION_FILE";
+
+/// Reads the service configuration from the common configuration template.
+///
+/// Falls back to the defaults if the template has no section of the service,
+/// e.g. if it has been generated without the service.
+fn template_config(context: &Context) -> Result<RngConfig, Error> {
+    let common = context.get(keys::COMMON_CONFIG)?;
+    parse_config(common.services_config.get(SERVICE_NAME))
+}
+
+fn parse_config(value: Option<&Value>) -> Result<RngConfig, Error> {
+    let config: RngConfig = match value {
+        Some(value) => value
+            .clone()
+            .try_into()
+            .map_err(|e| format_err!("Malformed configuration of {}: {}", SERVICE_NAME, e))?,
+        None => RngConfig::default(),
+    };
+    config.validate()?;
+    Ok(config)
+}
+
+/// Parses value of a numeric argument, the argument that is not given is `None`.
+fn parse_number(context: &Context, name: &str) -> Result<Option<u64>, Error> {
+    match context.arg::<String>(name) {
+        Ok(value) => value
+            .parse()
+            .map(Some)
+            .map_err(|_| format_err!("Invalid value of {}: {}", name, value)),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Parses value of an enum argument by its serialized name, e.g. `wesolowski`.
+fn parse_variant<T: DeserializeOwned>(context: &Context, name: &str) -> Result<Option<T>, Error> {
+    match context.arg::<String>(name) {
+        Ok(value) => serde_json::from_value(value.clone().into())
+            .map(Some)
+            .map_err(|_| format_err!("Invalid value of {}: {}", name, value)),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Adds the service configuration to the common configuration template,
+/// so it ends up in the genesis block of the network.
+pub struct GenerateCommonConfig;
+
+impl CommandExtension for GenerateCommonConfig {
+    fn args(&self) -> Vec<Argument> {
+        vec![
+            Argument::new_named(
+                VDF_DIFFICULTY,
+                false,
+                "Number of sequential iterations of the VDF.",
+                None,
+                "rng-vdf-difficulty",
+                false,
+            ),
+            Argument::new_named(
+                VDF_BACKEND,
+                false,
+                "VDF backend: mimc, wesolowski or pietrzak.",
+                None,
+                "rng-vdf-backend",
+                false,
+            ),
+            Argument::new_named(
+                CONTRIBUTION_SOURCE,
+                false,
+                "Source of the seed contributions: os or signature.",
+                None,
+                "rng-contribution-source",
+                false,
+            ),
+            Argument::new_named(
+                COMMIT_TIMEOUT,
+                false,
+                "Number of blocks to agree on the seed of a round.",
+                None,
+                "rng-commit-timeout",
+                false,
+            ),
+            Argument::new_named(
+                VDF_TIMEOUT,
+                false,
+                "Number of blocks to publish VDF results of a round.",
+                None,
+                "rng-vdf-timeout",
+                false,
+            ),
+        ]
+    }
+
+    fn execute(&self, mut context: Context) -> Result<Context, Error> {
+        let mut config = RngConfig::default();
+        if let Some(difficulty) = parse_number(&context, VDF_DIFFICULTY)? {
+            config.vdf_difficulty = difficulty;
+        }
+        if let Some(backend) = parse_variant(&context, VDF_BACKEND)? {
+            config.vdf_backend = backend;
+        }
+        if let Some(source) = parse_variant(&context, CONTRIBUTION_SOURCE)? {
+            config.contribution_source = source;
+        }
+        if let Some(timeout) = parse_number(&context, COMMIT_TIMEOUT)? {
+            config.commit_timeout = timeout;
+        }
+        if let Some(timeout) = parse_number(&context, VDF_TIMEOUT)? {
+            config.vdf_timeout = timeout;
+        }
+
+        config.validate()?;
+
+        let mut services_config: BTreeMap<String, Value> = context.get(keys::SERVICES_CONFIG).unwrap_or_default();
+        services_config.insert(SERVICE_NAME.to_owned(), Value::try_from(config)?);
+        context.set(keys::SERVICES_CONFIG, services_config);
+        Ok(context)
+    }
+}
+
+/// Checks that the configuration template has a valid service configuration,
+/// so a malformed template is rejected before the keys of the node are generated.
+pub struct GenerateNodeConfig;
+
+impl CommandExtension for GenerateNodeConfig {
+    fn args(&self) -> Vec<Argument> {
+        Vec::new()
+    }
+
+    fn execute(&self, context: Context) -> Result<Context, Error> {
+        template_config(&context)?;
+        Ok(context)
+    }
+}
+
+/// Writes the service configuration from the template into the node configuration,
+/// from which the service puts it into the genesis block.
+pub struct Finalize;
+
+impl CommandExtension for Finalize {
+    fn args(&self) -> Vec<Argument> {
+        Vec::new()
+    }
+
+    fn execute(&self, mut context: Context) -> Result<Context, Error> {
+        let config = template_config(&context)?;
+
+        let mut node_config: NodeConfig = context.get(keys::NODE_CONFIG)?;
+        node_config
+            .services_configs
+            .insert(SERVICE_NAME.to_owned(), Value::try_from(config)?);
+        context.set(keys::NODE_CONFIG, node_config);
+        Ok(context)
+    }
+}
+
+/// Reads the service configuration from the node configuration.
+///
+/// Falls back to the defaults if the node configuration has no section of the service.
+pub fn node_config(context: &Context) -> Result<RngConfig, Error> {
+    let node_config: NodeConfig = context.get(keys::NODE_CONFIG)?;
+    parse_config(node_config.services_configs.get(SERVICE_NAME))
+}
+
+/// Adds paths of the local files with checkpoints of the VDF evaluation
+/// and seed contributions of the validator to `run`.
+pub struct Run;
+
+impl CommandExtension for Run {
+    fn args(&self) -> Vec<Argument> {
+        vec![
+            Argument::new_named(
+                VDF_CHECKPOINT,
+                false,
+                "Path to the file the progress of the VDF evaluation is saved to. \
+                 Only the wesolowski and pietrzak backends can be resumed, mimc ignores the file.",
+                None,
+                "rng-vdf-checkpoint",
+                false,
+            ),
+            Argument::new_named(
+                CONTRIBUTION_FILE,
+                false,
+                "Path to the file seed contributions are saved to until they are revealed. \
+                 Required with the os contribution source.",
+                None,
+                "rng-contribution-file",
+                false,
+            ),
+        ]
+    }
+
+    fn execute(&self, context: Context) -> Result<Context, Error> {
+        // Contribution kept only in memory is lost on a restart, so a commitment that is
+        // still pending at that moment would be followed by another one, which is recorded
+        // as an equivocation of the validator.
+        let config = node_config(&context)?;
+        if config.contribution_source == ContributionSource::Os && contribution_file(&context).is_none() {
+            bail!("--rng-contribution-file is required with the os contribution source");
+        }
+        Ok(context)
+    }
+}
+
+/// Returns checkpoint file of the VDF evaluation given to `run`, if any.
+pub fn vdf_checkpoints(context: &Context) -> Option<CheckpointFile> {
+    context.arg::<String>(VDF_CHECKPOINT).ok().map(CheckpointFile::new)
+}
+
+/// Returns file of the seed contributions given to `run`, if any.
+pub fn contribution_file(context: &Context) -> Option<ContributionFile> {
+    context.arg::<String>(CONTRIBUTION_FILE).ok().map(ContributionFile::new)
+}
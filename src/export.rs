@@ -0,0 +1,39 @@
+use serde_json;
+
+use std::fmt::Write;
+
+use blockchain::schema::RoundInfo;
+
+/// Header of the CSV export of the round history.
+pub const CSV_HEADER: &str =
+    "round,height,failed,seed,randomness,difficulty,vdf_backend,contributions,missing_validators";
+
+/// Formats rounds as CSV, one line per round after the `CSV_HEADER` line.
+///
+/// Lists are reduced to the number of their elements, use `rounds_to_json`
+/// to export the rounds in full.
+pub fn rounds_to_csv(rounds: &[RoundInfo]) -> String {
+    let mut csv = format!("{}\n", CSV_HEADER);
+    for info in rounds {
+        writeln!(
+            csv,
+            "{},{},{},{},{},{},{},{},{}",
+            info.round(),
+            info.height(),
+            info.failed(),
+            info.seed().to_hex(),
+            info.randomness().to_hex(),
+            info.difficulty(),
+            info.vdf_backend(),
+            info.commitments().len(),
+            info.missing_validators().len(),
+        ).expect("Writing to a string doesn't fail");
+    }
+    csv
+}
+
+/// Formats rounds as a JSON array in the format of the `v1/rounds` endpoint,
+/// so it can be read back as `Vec<RoundInfo>`.
+pub fn rounds_to_json(rounds: &[RoundInfo]) -> Result<String, serde_json::Error> {
+    serde_json::to_string_pretty(rounds)
+}
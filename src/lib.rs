@@ -3,18 +3,33 @@
 #[macro_use]
 extern crate exonum;
 extern crate serde;
+extern crate serde_json;
 #[macro_use]
 extern crate serde_derive;
 #[macro_use]
 extern crate failure;
+#[macro_use]
+extern crate lazy_static;
+#[macro_use]
+extern crate log;
 
+extern crate actix_web;
+extern crate futures;
 extern crate rand;
 extern crate rug;
+extern crate toml;
 extern crate vdf;
 
 pub mod api;
+pub mod backend;
+pub mod beacon;
 pub mod blockchain;
+pub mod export;
+pub mod metrics;
 pub mod rng;
+pub mod verify;
+mod cmd;
 mod service;
+mod worker;
 
 pub use service::{ExonumRngService, SERVICE_NAME, SERVICE_ID};
\ No newline at end of file
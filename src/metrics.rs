@@ -0,0 +1,238 @@
+use exonum::{
+    blockchain::{Schema as CoreSchema, TransactionErrorType, TransactionResult},
+    helpers::Height,
+    storage::Snapshot,
+};
+
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::Mutex,
+    time::Duration,
+};
+
+use blockchain::{errors::Error, schema::RngSchema};
+use SERVICE_ID;
+
+/// Reasons of the refused transactions, as they are labeled in the metrics.
+const REJECTION_REASONS: [(Error, &str); 17] = [
+    (Error::NotEnoughSeedCommitments, "not_enough_seed_commitments"),
+    (Error::NotAValidator, "not_a_validator"),
+    (Error::NoSeed, "no_seed"),
+    (Error::SeedMismatch, "seed_mismatch"),
+    (Error::InvalidVdf, "invalid_vdf"),
+    (Error::DuplicateSubmission, "duplicate_submission"),
+    (Error::WrongRound, "wrong_round"),
+    (Error::MalformedBigInt, "malformed_big_int"),
+    (Error::CommitPhaseOver, "commit_phase_over"),
+    (Error::NotCommitted, "not_committed"),
+    (Error::RevealMismatch, "reveal_mismatch"),
+    (Error::SeedAlreadyAgreed, "seed_already_agreed"),
+    (Error::InvalidContributionSignature, "invalid_contribution_signature"),
+    (Error::RoundIsOver, "round_is_over"),
+    (Error::TooManyRequests, "too_many_requests"),
+    (Error::RoundTooFar, "round_too_far"),
+    (Error::RoundIsFull, "round_is_full"),
+];
+
+lazy_static! {
+    static ref METRICS: Mutex<Metrics> = Mutex::new(Metrics::default());
+}
+
+/// Number and total duration of measured operations.
+#[derive(Debug, Default)]
+struct Timer {
+    count: u64,
+    total: Duration,
+    last: Duration,
+}
+
+impl Timer {
+    fn observe(&mut self, duration: Duration) {
+        self.count += 1;
+        self.total += duration;
+        self.last = duration;
+    }
+}
+
+/// Node-local measurements that can't be derived from the blockchain state.
+#[derive(Debug, Default)]
+struct Metrics {
+    vdf_evaluation: Timer,
+    vdf_verification: Timer,
+    rejected_submissions: BTreeMap<&'static str, u64>,
+}
+
+/// Records wall time of the VDF evaluation by the worker.
+pub(crate) fn observe_vdf_evaluation(duration: Duration) {
+    METRICS.lock().unwrap().vdf_evaluation.observe(duration);
+}
+
+/// Records time of the VDF verification in `TxPublishVdfResult::execute`.
+///
+/// Transactions are executed for every proposed block, so it's the time the node
+/// spends on verification rather than the time per committed result.
+pub(crate) fn observe_vdf_verification(duration: Duration) {
+    METRICS.lock().unwrap().vdf_verification.observe(duration);
+}
+
+/// Counts transactions of the service refused in the latest committed block by reason.
+///
+/// Should be called once per committed block, i.e. in `after_commit`.
+pub(crate) fn observe_committed_block<T: AsRef<Snapshot>>(snapshot: T) {
+    let core_schema = CoreSchema::new(snapshot);
+    let height = Height(core_schema.block_hashes_by_height().len() - 1);
+    let transactions = core_schema.transactions();
+    let results = core_schema.transaction_results();
+
+    let mut metrics = METRICS.lock().unwrap();
+    for tx_hash in core_schema.block_transactions(height).iter() {
+        let is_ours = transactions
+            .get(&tx_hash)
+            .map_or(false, |raw| raw.service_id() == SERVICE_ID);
+        let code = match results.get(&tx_hash) {
+            Some(TransactionResult(Err(ref error))) if is_ours => error.error_type(),
+            _ => continue,
+        };
+
+        let reason = REJECTION_REASONS
+            .iter()
+            .find(|&&(error, _)| TransactionErrorType::Code(error as u8) == code)
+            .map_or("other", |&(_, reason)| reason);
+        *metrics.rejected_submissions.entry(reason).or_insert(0) += 1;
+    }
+}
+
+fn seconds(duration: Duration) -> f64 {
+    duration.as_secs() as f64 + f64::from(duration.subsec_nanos()) / 1e9
+}
+
+fn write_metric(out: &mut String, name: &str, kind: &str, help: &str, samples: &[(&str, f64)]) {
+    writeln!(out, "# HELP {} {}", name, help).unwrap();
+    writeln!(out, "# TYPE {} {}", name, kind).unwrap();
+    for &(labels, value) in samples {
+        writeln!(out, "{}{} {}", name, labels, value).unwrap();
+    }
+}
+
+fn write_timer(out: &mut String, name: &str, help: &str, timer: &Timer) {
+    write_metric(
+        out,
+        name,
+        "summary",
+        help,
+        &[
+            ("_sum", seconds(timer.total)),
+            ("_count", timer.count as f64),
+        ],
+    );
+    write_metric(
+        out,
+        &format!("{}_last", name),
+        "gauge",
+        "Duration of the latest measurement in seconds.",
+        &[("", seconds(timer.last))],
+    );
+}
+
+/// Renders metrics of the beacon in the Prometheus text exposition format.
+///
+/// Round metrics are read from the `snapshot`, timings and rejections are the ones
+/// observed by this node since it has started.
+pub fn render<T: AsRef<Snapshot>>(snapshot: T) -> String {
+    let schema = RngSchema::new(snapshot);
+    let mut out = String::new();
+
+    write_metric(
+        &mut out,
+        "exonum_rng_round",
+        "gauge",
+        "Number of the current round.",
+        &[("", schema.current_round() as f64)],
+    );
+
+    let phase = if schema.last_seed().exists() {
+        "vdf"
+    } else if schema.is_commit_phase_over() {
+        "reveal"
+    } else {
+        "commit"
+    };
+    let phases = ["commit", "reveal", "vdf"]
+        .iter()
+        .map(|&name| (format!("{{phase=\"{}\"}}", name), if name == phase { 1.0 } else { 0.0 }))
+        .collect::<Vec<_>>();
+    write_metric(
+        &mut out,
+        "exonum_rng_round_phase",
+        "gauge",
+        "Phase of the current round, 1 for the current one.",
+        &phases.iter().map(|&(ref labels, value)| (labels.as_str(), value)).collect::<Vec<_>>(),
+    );
+
+    write_metric(
+        &mut out,
+        "exonum_rng_round_commitments",
+        "gauge",
+        "Number of seed commitments in the current round.",
+        &[("", schema.num_seed_commitments() as f64)],
+    );
+
+    let rounds = schema.rounds();
+    if let Some(last) = rounds.last() {
+        let start = match last.round() {
+            0 => 0,
+            round => rounds.get(round - 1).map_or(0, |previous| previous.height().0),
+        };
+        write_metric(
+            &mut out,
+            "exonum_rng_last_round_duration_blocks",
+            "gauge",
+            "Number of blocks the latest ended round has taken.",
+            &[("", last.height().0.saturating_sub(start) as f64)],
+        );
+        write_metric(
+            &mut out,
+            "exonum_rng_last_round_commitments",
+            "gauge",
+            "Number of revealed seed commitments in the latest ended round.",
+            &[("", last.commitments().len() as f64)],
+        );
+        write_metric(
+            &mut out,
+            "exonum_rng_last_round_failed",
+            "gauge",
+            "1 if the latest ended round has been aborted.",
+            &[("", if last.failed() { 1.0 } else { 0.0 })],
+        );
+    }
+
+    let metrics = METRICS.lock().unwrap();
+    write_timer(
+        &mut out,
+        "exonum_rng_vdf_evaluation_seconds",
+        "Wall time of the VDF evaluation by this validator.",
+        &metrics.vdf_evaluation,
+    );
+    write_timer(
+        &mut out,
+        "exonum_rng_vdf_verification_seconds",
+        "Time of the VDF verification in the transaction execution.",
+        &metrics.vdf_verification,
+    );
+
+    let rejected = metrics
+        .rejected_submissions
+        .iter()
+        .map(|(reason, &count)| (format!("{{reason=\"{}\"}}", reason), count as f64))
+        .collect::<Vec<_>>();
+    write_metric(
+        &mut out,
+        "exonum_rng_rejected_submissions_total",
+        "counter",
+        "Number of the service transactions refused in the committed blocks, by reason.",
+        &rejected.iter().map(|&(ref labels, value)| (labels.as_str(), value)).collect::<Vec<_>>(),
+    );
+
+    out
+}
@@ -0,0 +1,177 @@
+use exonum::crypto::Hash;
+use rug::Integer;
+use serde_json;
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use backend::{Checkpoints, VdfBackendKind};
+use blockchain::bigint::BigInt;
+
+/// Number of squarings between checkpoints, unless configured otherwise.
+pub const DEFAULT_CHECKPOINT_INTERVAL: u64 = 1 << 20;
+
+/// Intermediate state of the evaluation of the VDF of a round seed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VdfCheckpoint {
+    /// Seed the VDF is evaluated for.
+    pub seed: Hash,
+
+    /// Backend that evaluates the VDF.
+    pub backend: VdfBackendKind,
+
+    /// Number of squarings done.
+    pub iterations: u64,
+
+    /// Value after `iterations` squarings.
+    pub value: BigInt,
+
+    /// Powers of the input the proof is built from that have been passed
+    /// by `iterations` squarings, there are as many of them as proof powers kept
+    /// by the backend at most.
+    #[serde(default)]
+    pub powers: Vec<BigInt>,
+}
+
+/// Local file with the latest checkpoint of the VDF evaluation.
+///
+/// The file is not a part of the blockchain state, so every validator keeps its own
+/// and trusts it only to save time: output of a resumed evaluation is verified
+/// before it's published.
+#[derive(Debug, Clone)]
+pub struct CheckpointFile {
+    path: PathBuf,
+    interval: u64,
+}
+
+impl CheckpointFile {
+    /// Creates checkpoint file at the `path` that is saved every `DEFAULT_CHECKPOINT_INTERVAL`
+    /// squarings.
+    pub fn new<P: Into<PathBuf>>(path: P) -> CheckpointFile {
+        CheckpointFile {
+            path: path.into(),
+            interval: DEFAULT_CHECKPOINT_INTERVAL,
+        }
+    }
+
+    /// Sets number of squarings between checkpoints.
+    pub fn with_interval(mut self, interval: u64) -> CheckpointFile {
+        self.interval = interval;
+        self
+    }
+
+    /// Returns path of the file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the saved checkpoint.
+    ///
+    /// Returns `None` if there's no checkpoint or it can't be read.
+    pub fn load(&self) -> Option<VdfCheckpoint> {
+        let contents = fs::read(&self.path).ok()?;
+        serde_json::from_slice(&contents).ok()
+    }
+
+    /// Replaces the saved checkpoint.
+    ///
+    /// The checkpoint is written into a temporary file that is renamed afterwards,
+    /// so a restart during the write doesn't corrupt the previous checkpoint.
+    pub fn store(&self, checkpoint: &VdfCheckpoint) -> io::Result<()> {
+        let contents = serde_json::to_vec(checkpoint)?;
+        let tmp_path = self.path.with_extension("tmp");
+        fs::write(&tmp_path, contents)?;
+        fs::rename(&tmp_path, &self.path)
+    }
+
+    /// Removes the saved checkpoint, if any.
+    pub fn clear(&self) -> io::Result<()> {
+        match fs::remove_file(&self.path) {
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            result => result,
+        }
+    }
+
+    /// Returns checkpoints of the evaluation of the `seed` by the `backend`.
+    pub(crate) fn for_seed(&self, seed: Hash, backend: VdfBackendKind) -> SeedCheckpoints {
+        SeedCheckpoints {
+            file: self,
+            seed,
+            backend,
+            restored: false,
+        }
+    }
+}
+
+/// Checkpoints of the evaluation of a single seed stored in a `CheckpointFile`.
+///
+/// Checkpoints of other seeds or backends are ignored and overwritten.
+pub(crate) struct SeedCheckpoints<'a> {
+    file: &'a CheckpointFile,
+    seed: Hash,
+    backend: VdfBackendKind,
+    restored: bool,
+}
+
+impl<'a> SeedCheckpoints<'a> {
+    /// Returns `true` if the evaluation has been resumed from a saved checkpoint.
+    pub fn is_restored(&self) -> bool {
+        self.restored
+    }
+}
+
+impl<'a> Checkpoints for SeedCheckpoints<'a> {
+    fn restore(&mut self) -> Option<(u64, Integer, Vec<Integer>)> {
+        let checkpoint = self.file.load()?;
+        if checkpoint.seed != self.seed || checkpoint.backend != self.backend {
+            return None;
+        }
+
+        info!(
+            "Resuming VDF calculation for seed {} from iteration {}",
+            self.seed.to_hex(),
+            checkpoint.iterations
+        );
+        self.restored = true;
+        let powers = checkpoint.powers.into_iter().map(BigInt::into_integer).collect();
+        Some((checkpoint.iterations, checkpoint.value.into_integer(), powers))
+    }
+
+    fn save(&mut self, iterations: u64, value: &Integer, powers: &[Integer]) {
+        let encoded = BigInt::new(value.clone()).and_then(|value| {
+            let powers = powers
+                .iter()
+                .map(|power| BigInt::new(power.clone()))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok((value, powers))
+        });
+        let (value, powers) = match encoded {
+            Ok(encoded) => encoded,
+            Err(e) => {
+                error!("Unable to save VDF checkpoint: {}", e);
+                return;
+            }
+        };
+
+        let checkpoint = VdfCheckpoint {
+            seed: self.seed,
+            backend: self.backend,
+            iterations,
+            value,
+            powers,
+        };
+        if let Err(e) = self.file.store(&checkpoint) {
+            error!(
+                "Unable to save VDF checkpoint to {}: {}",
+                self.file.path.display(),
+                e
+            );
+        }
+    }
+
+    fn interval(&self) -> u64 {
+        self.file.interval
+    }
+}
@@ -0,0 +1,72 @@
+use exonum::crypto::Hash;
+use serde_json;
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use blockchain::bigint::BigInt;
+
+/// Seed contribution of this validator to a round along with the salt it's committed with.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoredContribution {
+    /// Round the contribution is made to.
+    pub round: u64,
+
+    /// Value of the contribution.
+    pub value: BigInt,
+
+    /// Salt of the commitment.
+    pub salt: Hash,
+}
+
+/// Local file with the seed contribution of this validator to the current round.
+///
+/// The contribution is saved before its commitment is published, so the validator
+/// is able to reveal it even if it's restarted in between. Otherwise the round would
+/// stall until its deadline once the commit phase is closed.
+#[derive(Debug, Clone)]
+pub struct ContributionFile {
+    path: PathBuf,
+}
+
+impl ContributionFile {
+    /// Creates contribution file at the `path`.
+    pub fn new<P: Into<PathBuf>>(path: P) -> ContributionFile {
+        ContributionFile { path: path.into() }
+    }
+
+    /// Returns path of the file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the saved contribution to the `round`.
+    ///
+    /// Returns `None` if there's no contribution to the `round` or it can't be read.
+    pub fn load(&self, round: u64) -> Option<(BigInt, Hash)> {
+        let contents = fs::read(&self.path).ok()?;
+        let contribution: StoredContribution = serde_json::from_slice(&contents).ok()?;
+        if contribution.round != round {
+            return None;
+        }
+        Some((contribution.value, contribution.salt))
+    }
+
+    /// Replaces the saved contribution.
+    ///
+    /// Same as `CheckpointFile::store`, the contribution is written into a temporary file
+    /// that is renamed afterwards.
+    pub fn store(&self, round: u64, value: &BigInt, salt: &Hash) -> io::Result<()> {
+        let contribution = StoredContribution {
+            round,
+            value: value.clone(),
+            salt: *salt,
+        };
+        let contents = serde_json::to_vec(&contribution)?;
+        let tmp_path = self.path.with_extension("tmp");
+        fs::write(&tmp_path, contents)?;
+        fs::rename(&tmp_path, &self.path)
+    }
+}
@@ -0,0 +1,160 @@
+use exonum::crypto::{hash, Hash, HASH_SIZE};
+
+use backend::{put_u32_be, put_u64_be};
+
+/// Domain separator of the sub-randomness derivation.
+pub const DERIVE_DOMAIN: &[u8] = b"exonum_rng.derive.v1";
+
+/// Deterministic stream of random bytes derived from the randomness of a round.
+///
+/// Consumers pick a `label` identifying their application and purpose, and an `index`
+/// to get several independent streams for the same purpose. The stream is
+///
+/// ```text
+/// key = SHA-256(
+///     "exonum_rng.derive.v1"
+///     || randomness (32 bytes)
+///     || length of the label in bytes as u32 BE
+///     || label
+///     || index as u64 BE
+/// )
+/// stream = SHA-256(key || 0 as u64 BE) || SHA-256(key || 1 as u64 BE) || ...
+/// ```
+///
+/// All helpers consume the stream in a documented way, so every implementation
+/// derives identical values from the same randomness, label and index.
+#[derive(Debug, Clone)]
+pub struct RandomStream {
+    key: Hash,
+    counter: u64,
+    block: [u8; HASH_SIZE],
+    position: usize,
+}
+
+impl RandomStream {
+    /// Creates stream for the `label` and `index` from the `randomness` of a round.
+    pub fn new(randomness: &Hash, label: &[u8], index: u64) -> RandomStream {
+        let mut bytes = DERIVE_DOMAIN.to_vec();
+        bytes.extend_from_slice(randomness.as_ref());
+        put_u32_be(&mut bytes, label.len() as u32);
+        bytes.extend_from_slice(label);
+        put_u64_be(&mut bytes, index);
+
+        RandomStream {
+            key: hash(&bytes),
+            counter: 0,
+            block: [0; HASH_SIZE],
+            position: HASH_SIZE,
+        }
+    }
+
+    /// Fills `dest` with the next bytes of the stream.
+    pub fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for byte in dest.iter_mut() {
+            if self.position == HASH_SIZE {
+                self.next_block();
+            }
+
+            *byte = self.block[self.position];
+            self.position += 1;
+        }
+    }
+
+    /// Returns next `len` bytes of the stream.
+    pub fn next_bytes(&mut self, len: usize) -> Vec<u8> {
+        let mut bytes = vec![0; len];
+        self.fill_bytes(&mut bytes);
+        bytes
+    }
+
+    /// Returns next 8 bytes of the stream as a big-endian integer.
+    pub fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0; 8];
+        self.fill_bytes(&mut bytes);
+        bytes.iter().fold(0, |value, &byte| (value << 8) | u64::from(byte))
+    }
+
+    /// Returns integer uniformly distributed in `[low, high)`.
+    ///
+    /// Values of `next_u64` that don't fit into the largest multiple of the range size
+    /// are rejected and drawn again, so the result has no modulo bias.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `low >= high`.
+    pub fn gen_range(&mut self, low: u64, high: u64) -> u64 {
+        assert!(low < high, "Range is empty");
+
+        let range = high - low;
+        let zone = u64::max_value() - u64::max_value() % range;
+        loop {
+            let value = self.next_u64();
+            if value < zone {
+                return low + value % range;
+            }
+        }
+    }
+
+    /// Shuffles `items` with the Fisher-Yates algorithm: for `i` from `len - 1` down to `1`,
+    /// swaps the `i`th item with the `gen_range(0, i + 1)`th one.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.gen_range(0, i as u64 + 1) as usize;
+            items.swap(i, j);
+        }
+    }
+
+    /// Returns index of an item chosen with probability proportional to its weight.
+    ///
+    /// Draws `r = gen_range(0, total weight)` and returns the first index whose
+    /// cumulative weight exceeds `r`. Returns `None` if all weights are zero or their sum
+    /// overflows `u64`.
+    pub fn choose_weighted(&mut self, weights: &[u64]) -> Option<usize> {
+        let total = weights
+            .iter()
+            .try_fold(0u64, |total, &weight| total.checked_add(weight))?;
+        if total == 0 {
+            return None;
+        }
+
+        let mut target = self.gen_range(0, total);
+        for (i, &weight) in weights.iter().enumerate() {
+            if target < weight {
+                return Some(i);
+            }
+            target -= weight;
+        }
+
+        unreachable!("Target is less than the total weight")
+    }
+
+    /// Returns indices of up to `count` distinct items chosen with probability
+    /// proportional to their weights.
+    ///
+    /// Items are chosen one by one with `choose_weighted`, the weight of a chosen item
+    /// is set to zero before the next draw. Fewer than `count` indices are returned
+    /// if there's not enough items with non-zero weights.
+    pub fn sample_weighted(&mut self, weights: &[u64], count: usize) -> Vec<usize> {
+        let mut weights = weights.to_vec();
+        let mut chosen = Vec::with_capacity(count);
+        while chosen.len() < count {
+            match self.choose_weighted(&weights) {
+                Some(i) => {
+                    weights[i] = 0;
+                    chosen.push(i);
+                }
+                None => break,
+            }
+        }
+
+        chosen
+    }
+
+    fn next_block(&mut self) {
+        let mut bytes = self.key.as_ref().to_vec();
+        put_u64_be(&mut bytes, self.counter);
+        self.block.copy_from_slice(hash(&bytes).as_ref());
+        self.counter += 1;
+        self.position = 0;
+    }
+}
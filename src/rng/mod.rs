@@ -0,0 +1,234 @@
+mod checkpoint;
+mod contribution;
+mod derive;
+
+pub use self::checkpoint::{CheckpointFile, VdfCheckpoint, DEFAULT_CHECKPOINT_INTERVAL};
+pub use self::contribution::{ContributionFile, StoredContribution};
+pub use self::derive::{RandomStream, DERIVE_DOMAIN};
+
+use exonum::crypto::{self, Hash, PublicKey, SecretKey, Signature, SIGNATURE_LENGTH, hash};
+
+use blockchain::bigint::BigInt;
+
+use backend::{integer_from_bytes, put_u32_be, put_u64_be, VdfBackend, VdfOutput};
+use rand::{OsRng, Rng};
+use rug::Integer;
+use std::sync::atomic::AtomicBool;
+
+/// Number of random bytes in a seed contribution.
+pub const CONTRIBUTION_LENGTH: usize = 32;
+
+/// Domain separator of the messages signed to derive seed contributions.
+const CONTRIBUTION_DOMAIN: &[u8] = b"exonum_rng.contribution.v1";
+
+/// Domain separator of the messages signed to derive salts of signed contributions.
+const SALT_DOMAIN: &[u8] = b"exonum_rng.salt.v1";
+
+/// Domain separator of the combined seed encoding.
+pub const SEED_DOMAIN: &[u8] = b"exonum_rng.seed.v1";
+
+/// Domain separator of the round randomness encoding.
+pub const RANDOMNESS_DOMAIN: &[u8] = b"exonum_rng.randomness.v1";
+
+/// Returns `len` bytes from the OS random number generator.
+fn os_random_bytes(len: usize) -> Vec<u8> {
+    let mut rng = OsRng::new().expect("OS random number generator is unavailable");
+    let mut bytes = vec![0u8; len];
+    rng.fill_bytes(&mut bytes);
+    bytes
+}
+
+/// Returns message that is signed by a validator for the `round` with the given domain separator.
+fn round_message(domain: &[u8], round: u64) -> Vec<u8> {
+    let mut message = domain.to_vec();
+    put_u64_be(&mut message, round);
+    message
+}
+
+/// Generates seed contribution from 32 bytes of the OS random number generator.
+pub fn random_contribution() -> BigInt {
+    let value = integer_from_bytes(&os_random_bytes(CONTRIBUTION_LENGTH));
+    BigInt::new(value).expect("Contribution fits into a big integer")
+}
+
+/// Generates random salt for a seed commitment.
+pub fn random_salt() -> Hash {
+    Hash::from_slice(&os_random_bytes(32)).expect("Salt has the length of a hash")
+}
+
+/// Derives seed contribution of a validator to the `round` from its secret key.
+///
+/// The contribution is the Ed25519 signature of the
+/// `"exonum_rng.contribution.v1" || round as u64 BE` message, interpreted as a big-endian integer.
+/// Since the signing is deterministic, the contribution is reproducible after a restart
+/// and can be checked with `verify_signed_contribution` by anyone who knows
+/// the validator's public key.
+///
+/// The check only proves that the contribution is signed by the validator. Ed25519 signatures
+/// are not unique: a validator that picks its own nonces can produce any number of valid
+/// contributions and choose among them, so signed contributions are not more resistant
+/// to bias than random ones. A unique signature or VRF scheme would be needed for that.
+pub fn signed_contribution(round: u64, secret_key: &SecretKey) -> BigInt {
+    let signature = crypto::sign(&round_message(CONTRIBUTION_DOMAIN, round), secret_key);
+    BigInt::new(integer_from_bytes(signature.as_ref())).expect("Signature fits into a big integer")
+}
+
+/// Derives salt for the signed contribution to the `round`, so it's reproducible as well.
+pub fn signed_salt(round: u64, secret_key: &SecretKey) -> Hash {
+    let signature = crypto::sign(&round_message(SALT_DOMAIN, round), secret_key);
+    hash(signature.as_ref())
+}
+
+/// Checks that `value` is the contribution of the validator with `public_key`
+/// to the `round` derived with `signed_contribution`.
+pub fn verify_signed_contribution(value: &BigInt, round: u64, public_key: &PublicKey) -> bool {
+    let bytes = value.to_bytes();
+    if bytes.len() > SIGNATURE_LENGTH {
+        return false;
+    }
+
+    let mut signature_bytes = vec![0u8; SIGNATURE_LENGTH - bytes.len()];
+    signature_bytes.extend_from_slice(&bytes);
+
+    match Signature::from_slice(&signature_bytes) {
+        Some(signature) => crypto::verify(&signature, &round_message(CONTRIBUTION_DOMAIN, round), public_key),
+        None => false,
+    }
+}
+
+/// Calculates commitment to a seed contribution that is published in the commit phase.
+///
+/// `commitment = hash(value || salt)`, where `value` is encoded with `BigInt::to_bytes`.
+pub fn calculate_seed_commitment(value: &BigInt, salt: &Hash) -> Hash {
+    let mut bytes = value.to_bytes();
+    bytes.extend_from_slice(salt.as_ref());
+
+    hash(&bytes)
+}
+
+/// Calculates combined seed of the `round` from revealed contributions of the validators.
+///
+/// Contributions are ordered by the public key of the validator, so the order
+/// of `contributions` doesn't matter. The seed is the SHA-256 hash of
+///
+/// ```text
+/// "exonum_rng.seed.v1"
+/// || round as u64 BE
+/// || number of contributions as u32 BE
+/// || for each contribution:
+///        validator public key (32 bytes)
+///        || length of the value in bytes as u32 BE
+///        || value encoded with `BigInt::to_bytes`
+/// ```
+///
+/// Every variable-length field is prefixed with its length, so distinct sets
+/// of contributions can't produce the same encoding.
+pub fn calculate_combined_seed(round: u64, contributions: &[(PublicKey, BigInt)]) -> Hash {
+    let mut contributions = contributions.iter().collect::<Vec<_>>();
+    contributions.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut bytes = SEED_DOMAIN.to_vec();
+    put_u64_be(&mut bytes, round);
+    put_u32_be(&mut bytes, contributions.len() as u32);
+    for (pub_key, value) in contributions {
+        let value = value.to_bytes();
+        bytes.extend_from_slice(pub_key.as_ref());
+        put_u32_be(&mut bytes, value.len() as u32);
+        bytes.extend_from_slice(&value);
+    }
+
+    hash(&bytes)
+}
+
+/// Calculates randomness of the `round` from its seed and the accepted VDF output.
+///
+/// The randomness is the SHA-256 hash of
+///
+/// ```text
+/// "exonum_rng.randomness.v1"
+/// || round as u64 BE
+/// || seed (32 bytes)
+/// || length of the VDF output in bytes as u32 BE
+/// || VDF output encoded with `BigInt::to_bytes`
+/// ```
+///
+/// Since the VDF output is uniquely determined by the seed, so is the randomness.
+pub fn calculate_randomness(round: u64, seed: &Hash, vdf_output: &BigInt) -> Hash {
+    let output = vdf_output.to_bytes();
+
+    let mut bytes = RANDOMNESS_DOMAIN.to_vec();
+    put_u64_be(&mut bytes, round);
+    bytes.extend_from_slice(seed.as_ref());
+    put_u32_be(&mut bytes, output.len() as u32);
+    bytes.extend_from_slice(&output);
+
+    hash(&bytes)
+}
+
+/// Converts seed into the VDF challenge.
+fn seed_to_challenge(seed: &Hash) -> Option<Integer> {
+    Integer::from_str_radix(&seed.to_hex(), 16).ok()
+}
+
+/// Validates VDF output against the provided seed.
+///
+/// In according to VDF properties, verification should be much faster than calculation of VDF,
+/// so can be verified in transaction contract body.
+///
+/// `backend` and `difficulty` should be taken from `RngSchema::vdf_backend` and
+/// `RngSchema::vdf_difficulty` of the round the value belongs to.
+pub fn validate_vdf(backend: &dyn VdfBackend, seed: &Hash, output: &VdfOutput, difficulty: u64) -> bool {
+    match seed_to_challenge(seed) {
+        Some(challenge) => backend.verify(&challenge, difficulty, output),
+        None => false,
+    }
+}
+
+/// Calculates VDF against provided `seed`.
+///
+/// In according to VDF properties, calculation is deliberately made slow and sequential,
+/// to provide verifiable delay.
+///
+/// Since VDFs are supposed to be slow, VDF shouldn't be executed in transaction contract body
+/// and shouldn't be executed by validators that are fast-forwarding to the
+/// current blockchain tip when synchronizing with others.
+pub fn calculate_vdf(backend: &dyn VdfBackend, seed: &Hash, difficulty: u64) -> Option<VdfOutput> {
+    seed_to_challenge(seed).map(|challenge| backend.eval(&challenge, difficulty))
+}
+
+/// Same as `calculate_vdf`, but gives up and returns `None` as soon as `cancelled` is set.
+pub fn calculate_vdf_cancellable(
+    backend: &dyn VdfBackend,
+    seed: &Hash,
+    difficulty: u64,
+    cancelled: &AtomicBool,
+) -> Option<VdfOutput> {
+    seed_to_challenge(seed).and_then(|challenge| backend.eval_cancellable(&challenge, difficulty, cancelled))
+}
+
+/// Same as `calculate_vdf_cancellable`, but resumes the evaluation from the checkpoint
+/// of the `seed` saved in the `checkpoints` file, and saves its progress there.
+///
+/// The checkpoint is local and not trusted: if the output resumed from it doesn't pass
+/// verification, the checkpoint is discarded and the VDF is calculated from scratch.
+pub fn calculate_vdf_resumable(
+    backend: &dyn VdfBackend,
+    seed: &Hash,
+    difficulty: u64,
+    cancelled: &AtomicBool,
+    checkpoints: &CheckpointFile,
+) -> Option<VdfOutput> {
+    let challenge = seed_to_challenge(seed)?;
+    let kind = backend.parameters().kind;
+    let mut seed_checkpoints = checkpoints.for_seed(*seed, kind);
+    let output = backend.eval_resumable(&challenge, difficulty, cancelled, &mut seed_checkpoints)?;
+    if !seed_checkpoints.is_restored() || backend.verify(&challenge, difficulty, &output) {
+        return Some(output);
+    }
+
+    warn!("Discarding invalid VDF checkpoint for seed {}", seed.to_hex());
+    if let Err(e) = checkpoints.clear() {
+        error!("Unable to remove VDF checkpoint: {}", e);
+    }
+    backend.eval_resumable(&challenge, difficulty, cancelled, &mut checkpoints.for_seed(*seed, kind))
+}
@@ -1,30 +1,190 @@
-use api::PublicApi;
+use api::{PrivateApi, PublicApi};
 use exonum::{
     api::ServiceApiBuilder,
-    helpers::fabric::{ServiceFactory, Context},
-    crypto::Hash,
-    storage::Snapshot,
-    blockchain::{Transaction, TransactionSet, Service, ServiceContext},
+    helpers::{fabric::{self, CommandExtension, CommandName, Context, ServiceFactory}, Height},
+    crypto::{Hash, SecretKey},
+    storage::{Fork, Snapshot},
+    blockchain::{Schema as CoreSchema, Transaction, TransactionSet, Service, ServiceContext},
     messages::RawTransaction,
     encoding,
 };
+use serde_json::{self, Value};
+
 use blockchain::{
-    transactions::{ExonumRngTransactions, TxPublishVdfResult, TxPublishSeedCommitment},
+    config::{ContributionSource, RngConfig},
+    evidence::{record_equivocations, record_invalid_vdf_results},
+    transactions::{ExonumRngTransactions, TxPublishSeedCommitment, TxRevealSeed},
+    bigint::BigInt,
     schema::RngSchema,
 };
 
-use rng::calculate_vdf;
-use rand::{self, Rng};
+use cmd;
+use metrics;
+use rng::{
+    calculate_seed_commitment, random_contribution, random_salt, signed_contribution, signed_salt,
+    CheckpointFile, ContributionFile,
+};
+use worker::VdfWorker;
+
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::{Duration, SystemTime},
+};
 
 pub const SERVICE_ID: u16 = 9000;
 pub const SERVICE_NAME: &str = "exonum_rng";
 
-#[derive(Debug, Default)]
-pub struct ExonumRngService;
+/// Lag of the latest block behind the local clock after which the node is considered
+/// to be catching up with the network, unless configured otherwise.
+pub const DEFAULT_SYNC_LAG: Duration = Duration::from_secs(30);
+
+#[derive(Debug)]
+pub struct ExonumRngService {
+    /// Configuration written into the genesis block.
+    config: RngConfig,
+
+    /// Seed contributions of this validator and their salts, indexed by the round number.
+    ///
+    /// Kept until the contribution is revealed in the reveal phase.
+    contributions: Mutex<BTreeMap<u64, (BigInt, Hash)>>,
+
+    /// Local file the contributions from the OS random number generator are saved to,
+    /// so they survive a restart of the node.
+    contribution_file: Option<ContributionFile>,
+
+    /// Background worker that calculates VDF of the round seeds.
+    worker: VdfWorker,
+
+    /// Lag of the latest block behind the local clock after which the node is syncing.
+    sync_lag: Duration,
+
+    /// Whether the node has been syncing at the latest commit, used to log transitions.
+    syncing: AtomicBool,
+}
 
 impl ExonumRngService {
     pub fn new() -> ExonumRngService {
-        ExonumRngService
+        ExonumRngService::with_config(RngConfig::default())
+    }
+
+    /// Creates service that puts provided configuration into the genesis block.
+    pub fn with_config(config: RngConfig) -> ExonumRngService {
+        ExonumRngService {
+            config,
+            contributions: Mutex::new(BTreeMap::new()),
+            contribution_file: None,
+            worker: VdfWorker::new(),
+            sync_lag: DEFAULT_SYNC_LAG,
+            syncing: AtomicBool::new(false),
+        }
+    }
+
+    /// Saves progress of the VDF evaluation to the local `checkpoints` file,
+    /// so the evaluation is resumed rather than restarted after a restart of the node.
+    pub fn with_vdf_checkpoints(mut self, checkpoints: CheckpointFile) -> ExonumRngService {
+        self.worker = VdfWorker::with_checkpoints(checkpoints);
+        self
+    }
+
+    /// Saves seed contributions of this validator to the local `contributions` file
+    /// before committing to them, so they can be revealed after a restart of the node.
+    ///
+    /// Without the file, a commitment that is pending at a restart is followed by another one,
+    /// which is recorded as an equivocation, so `run` requires the file with OS contributions.
+    /// Signed contributions are derived from the secret key and don't need the file.
+    pub fn with_contribution_file(mut self, contributions: ContributionFile) -> ExonumRngService {
+        self.contribution_file = Some(contributions);
+        self
+    }
+
+    /// Sets lag of the latest block behind the local clock after which the node
+    /// is considered to be catching up with the network.
+    pub fn with_sync_lag(mut self, sync_lag: Duration) -> ExonumRngService {
+        self.sync_lag = sync_lag;
+        self
+    }
+
+    /// Returns `true` if the node is catching up with the network rather than
+    /// following its tip.
+    ///
+    /// Blocks replayed while syncing were committed long ago, so the node is syncing
+    /// if the latest precommit for the latest block is older than `sync_lag`.
+    fn is_syncing(&self, snapshot: &Snapshot) -> bool {
+        let schema = CoreSchema::new(snapshot);
+        let height = Height(schema.block_hashes_by_height().len() - 1);
+        let latest_precommit = schema
+            .block_and_precommits(height)
+            .and_then(|proof| proof.precommits.iter().map(|precommit| precommit.time()).max());
+
+        match latest_precommit {
+            // Precommits from the future mean that the local clock lags behind, not the node.
+            Some(time) => SystemTime::now()
+                .duration_since(time)
+                .map(|lag| lag > self.sync_lag)
+                .unwrap_or(false),
+            // Genesis block has no precommits.
+            None => false,
+        }
+    }
+
+    /// Returns contribution of this validator to the `round`, creating a new one if needed.
+    ///
+    /// New contribution from the OS random number generator is saved to the contribution file
+    /// first. Returns `None` if it can't be saved, since committing to a contribution
+    /// that may be lost would stall the round until its deadline.
+    fn contribution(&self, round: u64, source: ContributionSource, secret_key: &SecretKey) -> Option<(BigInt, Hash)> {
+        if let Some(contribution) = self.committed_contribution(round, source, secret_key) {
+            return Some(contribution);
+        }
+
+        let contribution = (random_contribution(), random_salt());
+        if let Some(ref file) = self.contribution_file {
+            if let Err(e) = file.store(round, &contribution.0, &contribution.1) {
+                error!(
+                    "Unable to save seed contribution to {}: {}",
+                    file.path().display(),
+                    e
+                );
+                return None;
+            }
+        }
+
+        self.contributions.lock().unwrap().insert(round, contribution.clone());
+        Some(contribution)
+    }
+
+    /// Returns contribution of this validator to the `round` that may have been committed to.
+    ///
+    /// Returns `None` if the contribution is neither derived from the secret key nor known,
+    /// e.g. if it's been kept only in memory before a restart of the node.
+    fn committed_contribution(
+        &self,
+        round: u64,
+        source: ContributionSource,
+        secret_key: &SecretKey,
+    ) -> Option<(BigInt, Hash)> {
+        let mut contributions = self.contributions.lock().unwrap();
+
+        // Contributions to the previous rounds are not needed anymore
+        *contributions = contributions.split_off(&round);
+
+        if let Some(contribution) = contributions.get(&round) {
+            return Some(contribution.clone());
+        }
+
+        let contribution = match source {
+            ContributionSource::Os => self.contribution_file.as_ref()?.load(round)?,
+            ContributionSource::Signature => (
+                signed_contribution(round, secret_key),
+                signed_salt(round, secret_key),
+            ),
+        };
+        contributions.insert(round, contribution.clone());
+        Some(contribution)
     }
 }
 
@@ -47,65 +207,177 @@ impl Service for ExonumRngService {
         Ok(tx.into())
     }
 
+    fn initialize(&self, _fork: &mut Fork) -> Value {
+        serde_json::to_value(&self.config).unwrap()
+    }
+
+    /// Records evidence of the conflicting submissions and invalid VDF results refused
+    /// in the block and aborts the current round if the validator set has changed
+    /// or the round deadline has passed, so a new round is started even if validators stall.
+    fn before_commit(&self, fork: &mut Fork) {
+        // Height of the block that is being created now.
+        let height = Height(CoreSchema::new(&*fork).block_hashes_by_height().len());
+
+        for equivocation in record_equivocations(fork, height) {
+            warn!(
+                "Validator {} has made conflicting submissions in round {}",
+                equivocation.pub_key().to_hex(),
+                equivocation.round()
+            );
+        }
+        record_invalid_vdf_results(fork, height);
+
+        let mut schema = RngSchema::new(fork);
+        if schema.round_validators().is_empty() {
+            schema.fix_round_validators();
+        }
+        if !schema.round_commit_timeout().exists() {
+            schema.fix_round_timeouts();
+        }
+        if !schema.round_contribution_source().exists() {
+            schema.fix_round_contribution_source();
+        }
+
+        // Submissions of the open round are made by the previous validator set,
+        // so the round is restarted with the new one
+        if schema.is_validator_set_changed() {
+            let info = schema.restart_round(height);
+            info!(
+                "Round {} is restarted at height {} due to the validator set change",
+                info.round(),
+                height
+            );
+        } else if height >= schema.phase_deadline() {
+            let info = schema.abort_round(height);
+            warn!(
+                "Round {} is aborted at height {}, {} validators are missing",
+                info.round(),
+                height,
+                info.missing_validators().len()
+            );
+        }
+    }
+
     /// Creates transaction after commit of the block.
     fn after_commit(&self, context: &ServiceContext) {
+        metrics::observe_committed_block(context.snapshot());
+
         // The transaction must be created by the validator.
         if context.validator_id().is_none() {
             return;
         }
 
-        // If there were seed commitment from current validator,
-        // and there's a combined seed that is combined from commitments of majority of validators,
-        // then calculate our value of VDF and publish it.
+        // Rounds replayed while syncing are long over, so neither commitments nor VDF
+        // results of this validator are needed for them. Evaluating VDF of every replayed
+        // round would only delay the node, so it rejoins rounds once it reaches the tip.
+        let syncing = self.is_syncing(context.snapshot());
+        if self.syncing.swap(syncing, Ordering::Relaxed) != syncing {
+            if syncing {
+                info!("Skipping rounds until the node reaches the tip of the chain");
+            } else {
+                info!("Rejoining rounds at height {}", context.height());
+            }
+        }
+        if syncing {
+            self.worker.cancel_stale(None);
+            return;
+        }
+
+        // The round goes through three phases:
         //
-        // Otherwise, do nothing.
+        // 1. Validators publish commitments `hash(value || salt)` to their seed contributions
+        //    until majority of commitments is collected.
+        // 2. Committed validators reveal their contributions, so nobody can adjust
+        //    its own value after seeing the others.
+        // 3. When majority of contributions is revealed, there's a combined seed,
+        //    so calculate our value of VDF and publish it.
 
         let schema = RngSchema::new(context.snapshot());
+        let round = schema.current_round();
+        let (pub_key, sec_key) = (*context.public_key(), context.secret_key().clone());
+        let source = schema.contribution_source();
 
-        // Do nothing if there wasn't a commitment from current validator
-        if !schema.validators_seed_commitments().contains(&context.public_key()) {
-            // Send validator's seed commitment
-            let mut rng = rand::thread_rng();
-            let sc = rng.gen::<u64>().to_string();
-
-            //println!("Validator is sending seed commitment {}", sc);
-            let (pub_key, sec_key) = (*context.public_key(), context.secret_key().clone());
-            context
-                .transaction_sender()
-                .send(Box::new(TxPublishSeedCommitment::new(
-                    &pub_key,
-                    &sc,
-                    &sec_key,
-                )))
-                .unwrap();
-
-            return
-        }
-
-        if let Some(seed) = schema.last_seed().get() {
-            println!("Calculating VDF from seed...");
+        // Evaluation of the previous round's seed is not needed anymore
+        let seed = schema.last_seed().get();
+        self.worker.cancel_stale(seed.as_ref());
 
-            if let Some(value) = calculate_vdf(&seed) {
-                //println!("VDF calculated by {:?} = {}", *context.public_key(), value);
+        if !schema.is_commit_phase_over() {
+            // Send validator's seed commitment, if it wasn't already accepted
+            if !schema.validators_seed_commitments().contains(&pub_key) {
+                let (value, salt) = match self.contribution(round, source, &sec_key) {
+                    Some(contribution) => contribution,
+                    None => return,
+                };
+                let commitment = calculate_seed_commitment(&value, &salt);
 
-                let (pub_key, sec_key) = (*context.public_key(), context.secret_key().clone());
                 context
                     .transaction_sender()
-                    .send(Box::new(TxPublishVdfResult::new(
+                    .send(Box::new(TxPublishSeedCommitment::new(
                         &pub_key,
-                        &seed,
-                        &value,
+                        round,
+                        &commitment,
                         &sec_key,
                     )))
                     .unwrap();
-            } else {
-                println!("error: invalid seed value, can't be parsed as hex value in `rug::Integer`: {}", seed);
             }
+
+            return
+        }
+
+        let seed = match seed {
+            Some(seed) => seed,
+            None => {
+                // Reveal validator's contribution, if it has been committed. A contribution
+                // that is lost can't be revealed, and publishing another one would only
+                // be refused as not matching the commitment.
+                let contribution = if schema.validators_seed_commitments().contains(&pub_key)
+                    && !schema.validators_seed_reveals().contains(&pub_key)
+                {
+                    self.committed_contribution(round, source, &sec_key)
+                } else {
+                    None
+                };
+
+                if let Some((value, salt)) = contribution {
+
+                    context
+                        .transaction_sender()
+                        .send(Box::new(TxRevealSeed::new(
+                            &pub_key,
+                            round,
+                            &value.to_bytes(),
+                            &salt,
+                            &sec_key,
+                        )))
+                        .unwrap();
+                }
+
+                return
+            }
+        };
+
+        // VDF is calculated in the background, the worker publishes the result when it's ready
+        if !schema.validators_vdf_results().contains(&pub_key) {
+            self.worker.request(
+                seed,
+                schema.vdf_difficulty(),
+                schema.vdf_backend(),
+                (pub_key, sec_key),
+                Box::new(context.transaction_sender().clone()),
+                context.height(),
+            );
         }
     }
 
     fn wire_api(&self, builder: &mut ServiceApiBuilder) {
         PublicApi::wire(builder);
+        PrivateApi::wire(builder);
+    }
+}
+
+impl Default for ExonumRngService {
+    fn default() -> ExonumRngService {
+        ExonumRngService::new()
     }
 }
 
@@ -114,7 +386,37 @@ impl ServiceFactory for ExonumRngService {
         SERVICE_NAME
     }
 
-    fn make_service(&mut self, _: &Context) -> Box<Service> {
-        Box::new(ExonumRngService::new())
+    fn command(&mut self, command: CommandName) -> Option<Box<CommandExtension>> {
+        use exonum::helpers::fabric::Command;
+
+        match command {
+            v if v == fabric::GenerateCommonConfig.name() => Some(Box::new(cmd::GenerateCommonConfig)),
+            v if v == fabric::GenerateNodeConfig.name() => Some(Box::new(cmd::GenerateNodeConfig)),
+            v if v == fabric::Finalize.name() => Some(Box::new(cmd::Finalize)),
+            v if v == fabric::Run.name() => Some(Box::new(cmd::Run)),
+            _ => None,
+        }
+    }
+
+    /// Creates service with the configuration written into the node configuration by `finalize`.
+    fn make_service(&mut self, context: &Context) -> Box<Service> {
+        let config = cmd::node_config(context).expect("Invalid configuration of the rng service");
+        let vdf_backend = config.vdf_backend;
+        let mut service = ExonumRngService::with_config(config);
+        if let Some(contributions) = cmd::contribution_file(context) {
+            service = service.with_contribution_file(contributions);
+        }
+        match cmd::vdf_checkpoints(context) {
+            Some(checkpoints) => {
+                if !vdf_backend.backend().is_resumable() {
+                    warn!(
+                        "VDF backend {:?} can't be resumed, --rng-vdf-checkpoint has no effect with it",
+                        vdf_backend
+                    );
+                }
+                Box::new(service.with_vdf_checkpoints(checkpoints))
+            }
+            None => Box::new(service),
+        }
     }
 }
\ No newline at end of file
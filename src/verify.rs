@@ -0,0 +1,306 @@
+use exonum::crypto::{Hash, PublicKey};
+
+use std::collections::BTreeSet;
+
+use backend::{VdfBackendKind, VdfOutput};
+use blockchain::{config::ContributionSource, quorum, schema::RoundInfo};
+use rng::{
+    calculate_combined_seed, calculate_randomness, calculate_seed_commitment, validate_vdf,
+    verify_signed_contribution,
+};
+
+/// Checks performed by `verify_round`, in the order they are performed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Check {
+    /// Round has been finalized rather than aborted.
+    Finalized,
+    /// Round is made by the expected validator set, and its contributions are made
+    /// by distinct validators from the set.
+    Validators,
+    /// Number of contributions reaches the quorum of the validator set.
+    Quorum,
+    /// Revealed contributions match their commitments.
+    Commitments,
+    /// Contributions are signatures of the round, if they are required to be.
+    Signatures,
+    /// Seed is combined from the contributions.
+    Seed,
+    /// VDF output is the result of evaluation of the VDF of the seed.
+    Vdf,
+    /// VDF output is agreed on by the quorum of distinct validators from the validator set.
+    VdfQuorum,
+    /// Randomness is derived from the seed and the VDF output.
+    Randomness,
+}
+
+/// Reasons for a round to fail verification.
+#[derive(Debug, Clone, PartialEq, Fail, Serialize, Deserialize)]
+pub enum VerificationError {
+    /// Round has been aborted, so it has no randomness.
+    #[fail(display = "Round has been aborted.")]
+    RoundFailed,
+
+    /// Validator set recorded in the round differs from the expected one.
+    #[fail(display = "Round is made by an unexpected validator set.")]
+    UnexpectedValidatorSet,
+
+    /// Contribution or VDF result is made by a key that is not in the validator set.
+    #[fail(display = "Submission is made by an unknown validator {:?}.", _0)]
+    UnknownValidator(PublicKey),
+
+    /// Validator has made several contributions.
+    #[fail(display = "Validator {:?} has made several contributions.", _0)]
+    DuplicateContribution(PublicKey),
+
+    /// Number of contributions is less than the quorum.
+    #[fail(display = "Round has {} contributions, while {} are required.", found, required)]
+    NotEnoughContributions {
+        /// Number of contributions in the round.
+        found: usize,
+        /// Quorum of the validator set.
+        required: usize,
+    },
+
+    /// Contribution is not a canonically encoded big integer.
+    #[fail(display = "Contribution of {:?} is malformed.", _0)]
+    MalformedContribution(PublicKey),
+
+    /// Revealed contribution doesn't match the commitment.
+    #[fail(display = "Contribution of {:?} doesn't match its commitment.", _0)]
+    CommitmentMismatch(PublicKey),
+
+    /// Contribution source recorded in the round differs from the expected one.
+    #[fail(display = "Round is made with contribution source {}, while {} is expected.", actual, expected)]
+    UnexpectedContributionSource {
+        /// Identifier of the expected source, see `ContributionSource::id`.
+        expected: u8,
+        /// Identifier of the source recorded in the round.
+        actual: u8,
+    },
+
+    /// Contribution isn't a signature of the round by the validator.
+    #[fail(display = "Contribution of {:?} isn't a signature of the round.", _0)]
+    InvalidContributionSignature(PublicKey),
+
+    /// Recorded seed differs from the one combined from the contributions.
+    #[fail(display = "Seed {:?} differs from the combined seed {:?}.", actual, expected)]
+    SeedMismatch {
+        /// Seed combined from the contributions.
+        expected: Hash,
+        /// Seed recorded in the round.
+        actual: Hash,
+    },
+
+    /// Round refers to an unknown VDF backend.
+    #[fail(display = "Unknown VDF backend {}.", _0)]
+    UnknownVdfBackend(u8),
+
+    /// VDF output or its proof is malformed.
+    #[fail(display = "VDF output is malformed.")]
+    MalformedVdfOutput,
+
+    /// VDF output doesn't pass verification.
+    #[fail(display = "VDF output is invalid.")]
+    InvalidVdf,
+
+    /// Validator is recorded several times among the authors of the VDF output.
+    #[fail(display = "Validator {:?} has published several VDF results.", _0)]
+    DuplicateVdfResult(PublicKey),
+
+    /// Number of validators that have agreed on the VDF output is less than the quorum.
+    #[fail(display = "VDF output is agreed on by {} validators, while {} are required.", found, required)]
+    NotEnoughVdfResults {
+        /// Number of validators that have published the VDF output.
+        found: usize,
+        /// Quorum of the validator set.
+        required: usize,
+    },
+
+    /// Recorded randomness differs from the one derived from the seed and the VDF output.
+    #[fail(display = "Randomness {:?} differs from the derived randomness {:?}.", actual, expected)]
+    RandomnessMismatch {
+        /// Randomness derived from the seed and the VDF output.
+        expected: Hash,
+        /// Randomness recorded in the round.
+        actual: Hash,
+    },
+}
+
+/// Result of verification of a round.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Verdict {
+    /// Number of the verified round.
+    pub round: u64,
+
+    /// Checks that have passed.
+    pub passed: Vec<Check>,
+
+    /// Check that has failed and the reason, if any.
+    ///
+    /// Verification stops at the first failed check.
+    pub failed: Option<(Check, VerificationError)>,
+}
+
+impl Verdict {
+    /// Returns `true` if every check has passed.
+    pub fn is_valid(&self) -> bool {
+        self.failed.is_none()
+    }
+}
+
+/// Recomputes every step of the round from its record and checks it against
+/// the trusted `validators` and `contribution_source` of the round.
+///
+/// The validator set and the contribution source recorded in the round should match
+/// the trusted ones, so a forged round can't bring its own. It's enough to have the round
+/// from the API or a `RoundProof`, no node is needed.
+pub fn verify_round(info: &RoundInfo, validators: &[PublicKey], contribution_source: ContributionSource) -> Verdict {
+    let mut passed = Vec::new();
+    let failed = run_checks(info, validators, contribution_source, &mut passed).err();
+
+    Verdict {
+        round: info.round(),
+        passed,
+        failed,
+    }
+}
+
+fn run_checks(
+    info: &RoundInfo,
+    validators: &[PublicKey],
+    contribution_source: ContributionSource,
+    passed: &mut Vec<Check>,
+) -> Result<(), (Check, VerificationError)> {
+    let round = info.round();
+
+    if info.failed() {
+        return Err((Check::Finalized, VerificationError::RoundFailed));
+    }
+    passed.push(Check::Finalized);
+
+    let recorded = info.validators().into_iter().collect::<BTreeSet<_>>();
+    if recorded.len() != validators.len() || !validators.iter().all(|pub_key| recorded.contains(pub_key)) {
+        return Err((Check::Validators, VerificationError::UnexpectedValidatorSet));
+    }
+
+    let commitments = info.commitments();
+    let mut authors = BTreeSet::new();
+    for commitment in &commitments {
+        let pub_key = *commitment.pub_key();
+        if !validators.contains(&pub_key) {
+            return Err((Check::Validators, VerificationError::UnknownValidator(pub_key)));
+        }
+        if !authors.insert(pub_key) {
+            return Err((Check::Validators, VerificationError::DuplicateContribution(pub_key)));
+        }
+    }
+    passed.push(Check::Validators);
+
+    let required = quorum(validators.len());
+    if commitments.len() < required {
+        return Err((
+            Check::Quorum,
+            VerificationError::NotEnoughContributions {
+                found: commitments.len(),
+                required,
+            },
+        ));
+    }
+    passed.push(Check::Quorum);
+
+    let mut contributions = Vec::with_capacity(commitments.len());
+    for commitment in &commitments {
+        let pub_key = *commitment.pub_key();
+        let value = commitment.contribution().map_err(|_| {
+            (Check::Commitments, VerificationError::MalformedContribution(pub_key))
+        })?;
+
+        if calculate_seed_commitment(&value, commitment.salt()) != *commitment.commitment() {
+            return Err((Check::Commitments, VerificationError::CommitmentMismatch(pub_key)));
+        }
+        contributions.push((pub_key, value));
+    }
+    passed.push(Check::Commitments);
+
+    if info.contribution_source() != contribution_source.id() {
+        return Err((
+            Check::Signatures,
+            VerificationError::UnexpectedContributionSource {
+                expected: contribution_source.id(),
+                actual: info.contribution_source(),
+            },
+        ));
+    }
+    if contribution_source == ContributionSource::Signature {
+        for &(ref pub_key, ref value) in &contributions {
+            if !verify_signed_contribution(value, round, pub_key) {
+                return Err((
+                    Check::Signatures,
+                    VerificationError::InvalidContributionSignature(*pub_key),
+                ));
+            }
+        }
+    }
+    passed.push(Check::Signatures);
+
+    let seed = calculate_combined_seed(round, &contributions);
+    if seed != *info.seed() {
+        return Err((
+            Check::Seed,
+            VerificationError::SeedMismatch {
+                expected: seed,
+                actual: *info.seed(),
+            },
+        ));
+    }
+    passed.push(Check::Seed);
+
+    let value = info
+        .output()
+        .map_err(|_| (Check::Vdf, VerificationError::MalformedVdfOutput))?;
+    let backend = VdfBackendKind::from_id(info.vdf_backend())
+        .ok_or_else(|| (Check::Vdf, VerificationError::UnknownVdfBackend(info.vdf_backend())))?
+        .backend();
+    let output = VdfOutput::decode(info.vdf_output(), info.vdf_proof())
+        .ok_or_else(|| (Check::Vdf, VerificationError::MalformedVdfOutput))?;
+    if !validate_vdf(&*backend, &seed, &output, info.difficulty()) {
+        return Err((Check::Vdf, VerificationError::InvalidVdf));
+    }
+    passed.push(Check::Vdf);
+
+    let submitters = info.vdf_submitters();
+    let mut authors = BTreeSet::new();
+    for pub_key in &submitters {
+        if !validators.contains(pub_key) {
+            return Err((Check::VdfQuorum, VerificationError::UnknownValidator(*pub_key)));
+        }
+        if !authors.insert(*pub_key) {
+            return Err((Check::VdfQuorum, VerificationError::DuplicateVdfResult(*pub_key)));
+        }
+    }
+    if submitters.len() < required {
+        return Err((
+            Check::VdfQuorum,
+            VerificationError::NotEnoughVdfResults {
+                found: submitters.len(),
+                required,
+            },
+        ));
+    }
+    passed.push(Check::VdfQuorum);
+
+    let randomness = calculate_randomness(round, &seed, &value);
+    if randomness != *info.randomness() {
+        return Err((
+            Check::Randomness,
+            VerificationError::RandomnessMismatch {
+                expected: randomness,
+                actual: *info.randomness(),
+            },
+        ));
+    }
+    passed.push(Check::Randomness);
+
+    Ok(())
+}
@@ -0,0 +1,371 @@
+use exonum::{
+    crypto::{Hash, PublicKey, SecretKey},
+    helpers::Height,
+    node::TransactionSend,
+};
+
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{channel, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread,
+    time::Instant,
+};
+
+use backend::VdfBackendKind;
+use blockchain::transactions::TxPublishVdfResult;
+use metrics;
+use rng::{calculate_vdf_cancellable, calculate_vdf_resumable, CheckpointFile};
+
+/// Number of blocks after which the VDF result is published again if it's still not accepted,
+/// unless configured otherwise.
+pub const DEFAULT_RETRY_INTERVAL: u64 = 10;
+
+/// Result of the evaluation, set by the worker thread once it's published.
+type JobResult = Arc<Mutex<Option<TxPublishVdfResult>>>;
+
+/// Request to calculate VDF of the round seed and publish the result.
+struct VdfJob {
+    seed: Hash,
+    difficulty: u64,
+    backend: VdfBackendKind,
+    cancelled: Arc<AtomicBool>,
+    pub_key: PublicKey,
+    sec_key: SecretKey,
+    sender: Box<dyn TransactionSend>,
+    result: JobResult,
+}
+
+/// VDF evaluation that has been requested last.
+struct ActiveJob {
+    seed: Hash,
+    cancelled: Arc<AtomicBool>,
+    result: JobResult,
+
+    /// Height at which the result is known to be published last.
+    published_at: Option<Height>,
+}
+
+impl ActiveJob {
+    /// Publishes the result of the finished evaluation again if `retry_interval` blocks
+    /// have passed since it's been published last.
+    ///
+    /// Only the result that is lost on the way to the block can be recovered this way:
+    /// the same transaction refused in a block would be ignored by the node.
+    fn retry(&mut self, height: Height, retry_interval: u64, sender: &dyn TransactionSend) {
+        let tx = match *self.result.lock().unwrap() {
+            Some(ref tx) => tx.clone(),
+            None => return,
+        };
+
+        match self.published_at {
+            // Result has been published by the worker thread since the previous request
+            None => self.published_at = Some(height),
+            Some(published_at) if height.0 >= published_at.0.saturating_add(retry_interval) => {
+                info!("Publishing VDF result for seed {} again", self.seed.to_hex());
+                if let Err(e) = sender.send(Box::new(tx)) {
+                    error!("Unable to send VDF result: {}", e);
+                }
+                self.published_at = Some(height);
+            }
+            Some(_) => {}
+        }
+    }
+}
+
+/// Calculates VDF in a dedicated thread, so the commit of the block isn't blocked
+/// for the whole delay period.
+///
+/// Requests for the seed that is being calculated are ignored, and the evaluation
+/// of a stale seed is cancelled as soon as another seed is requested
+/// or the round moves on. Once the evaluation is finished, repeated requests
+/// for the same seed publish its result again every `retry_interval` blocks.
+///
+/// If the worker has a checkpoint file, the progress of the evaluation is saved there,
+/// so the evaluation of the same seed is resumed after a restart of the node.
+pub struct VdfWorker {
+    jobs: Mutex<Sender<VdfJob>>,
+    active: Mutex<Option<ActiveJob>>,
+    retry_interval: u64,
+}
+
+impl VdfWorker {
+    /// Spawns the worker thread.
+    pub fn new() -> VdfWorker {
+        VdfWorker::spawn(None)
+    }
+
+    /// Spawns the worker thread that saves progress of the evaluation to `checkpoints`.
+    pub fn with_checkpoints(checkpoints: CheckpointFile) -> VdfWorker {
+        VdfWorker::spawn(Some(checkpoints))
+    }
+
+    fn spawn(checkpoints: Option<CheckpointFile>) -> VdfWorker {
+        let (jobs, receiver) = channel();
+        thread::Builder::new()
+            .name("exonum-rng-vdf".to_owned())
+            .spawn(move || Self::run(&receiver, checkpoints.as_ref()))
+            .expect("Unable to spawn VDF worker thread");
+
+        VdfWorker {
+            jobs: Mutex::new(jobs),
+            active: Mutex::new(None),
+            retry_interval: DEFAULT_RETRY_INTERVAL,
+        }
+    }
+
+    /// Sets number of blocks after which the result is published again if it's still requested.
+    pub fn with_retry_interval(mut self, retry_interval: u64) -> VdfWorker {
+        self.retry_interval = retry_interval;
+        self
+    }
+
+    /// Requests calculation of VDF of the `seed` at the `height`, its result is published
+    /// by the validator with the given keys.
+    ///
+    /// Should be called on every block until the result is accepted, so the result
+    /// that has been lost is published again.
+    pub fn request(
+        &self,
+        seed: Hash,
+        difficulty: u64,
+        backend: VdfBackendKind,
+        keys: (PublicKey, SecretKey),
+        sender: Box<dyn TransactionSend>,
+        height: Height,
+    ) {
+        let mut active = self.active.lock().unwrap();
+        if let Some(ref mut job) = *active {
+            if job.seed == seed {
+                job.retry(height, self.retry_interval, &*sender);
+                return;
+            }
+        }
+
+        if let Some(job) = active.take() {
+            job.cancelled.store(true, Ordering::SeqCst);
+        }
+
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let result = JobResult::default();
+        let job = VdfJob {
+            seed,
+            difficulty,
+            backend,
+            cancelled: Arc::clone(&cancelled),
+            pub_key: keys.0,
+            sec_key: keys.1,
+            sender,
+            result: Arc::clone(&result),
+        };
+
+        if self.jobs.lock().unwrap().send(job).is_ok() {
+            *active = Some(ActiveJob {
+                seed,
+                cancelled,
+                result,
+                published_at: None,
+            });
+        } else {
+            error!("VDF worker thread has stopped");
+        }
+    }
+
+    /// Cancels evaluation of any seed other than `seed`.
+    pub fn cancel_stale(&self, seed: Option<&Hash>) {
+        let mut active = self.active.lock().unwrap();
+        let is_stale = active.as_ref().map_or(false, |job| Some(&job.seed) != seed);
+        if is_stale {
+            if let Some(job) = active.take() {
+                job.cancelled.store(true, Ordering::SeqCst);
+            }
+        }
+    }
+
+    fn run(receiver: &Receiver<VdfJob>, checkpoints: Option<&CheckpointFile>) {
+        for job in receiver.iter() {
+            info!("Calculating VDF from seed {}", job.seed.to_hex());
+
+            let backend = job.backend.backend();
+            if checkpoints.is_some() && !backend.is_resumable() {
+                warn!(
+                    "VDF backend {:?} can't be resumed, its progress is not saved to checkpoints",
+                    job.backend
+                );
+            }
+            let started = Instant::now();
+            let output = match checkpoints {
+                Some(checkpoints) => {
+                    calculate_vdf_resumable(&*backend, &job.seed, job.difficulty, &job.cancelled, checkpoints)
+                }
+                None => calculate_vdf_cancellable(&*backend, &job.seed, job.difficulty, &job.cancelled),
+            };
+            let output = match output {
+                Some(output) => output,
+                None => {
+                    info!("VDF calculation for seed {} is cancelled", job.seed.to_hex());
+                    continue;
+                }
+            };
+            metrics::observe_vdf_evaluation(started.elapsed());
+
+            let tx = TxPublishVdfResult::new(
+                &job.pub_key,
+                &job.seed,
+                &output.encoded_value(),
+                &output.encoded_proof(),
+                &job.sec_key,
+            );
+            // Result is recorded before it's sent, so a request that sees it sent can retry it
+            *job.result.lock().unwrap() = Some(tx.clone());
+            if let Err(e) = job.sender.send(Box::new(tx)) {
+                error!("Unable to send VDF result: {}", e);
+            }
+        }
+    }
+}
+
+impl Default for VdfWorker {
+    fn default() -> VdfWorker {
+        VdfWorker::new()
+    }
+}
+
+impl fmt::Debug for VdfWorker {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let active = self.active.lock().unwrap();
+        f.debug_struct("VdfWorker")
+            .field("active_seed", &active.as_ref().map(|job| job.seed))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use exonum::{
+        blockchain::Transaction,
+        crypto::{gen_keypair, hash, Hash, PublicKey, SecretKey},
+        helpers::Height,
+        messages::Message,
+        node::TransactionSend,
+    };
+
+    use std::{
+        io,
+        sync::{Arc, Mutex},
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use super::VdfWorker;
+    use backend::VdfBackendKind;
+    use blockchain::transactions::TxPublishVdfResult;
+    use rng::calculate_vdf;
+
+    const DIFFICULTY: u64 = 100;
+
+    /// Collects hashes of the sent transactions.
+    #[derive(Clone, Default)]
+    struct SentTransactions(Arc<Mutex<Vec<Hash>>>);
+
+    impl SentTransactions {
+        /// Waits until `count` transactions are sent and returns them.
+        fn wait_for(&self, count: usize) -> Vec<Hash> {
+            let started = Instant::now();
+            while self.0.lock().unwrap().len() < count {
+                assert!(started.elapsed() < Duration::from_secs(30), "VDF result isn't sent");
+                thread::sleep(Duration::from_millis(10));
+            }
+            self.0.lock().unwrap().clone()
+        }
+    }
+
+    impl TransactionSend for SentTransactions {
+        fn send(&self, tx: Box<dyn Transaction>) -> io::Result<()> {
+            self.0.lock().unwrap().push(tx.hash());
+            Ok(())
+        }
+    }
+
+    fn expected_tx(seed: &Hash, keys: &(PublicKey, SecretKey)) -> Hash {
+        let backend = VdfBackendKind::Mimc.backend();
+        let output = calculate_vdf(&*backend, seed, DIFFICULTY).unwrap();
+        TxPublishVdfResult::new(&keys.0, seed, &output.encoded_value(), &output.encoded_proof(), &keys.1).hash()
+    }
+
+    #[test]
+    fn test_requests_are_deduplicated() {
+        let worker = VdfWorker::new();
+        let sent = SentTransactions::default();
+        let keys = gen_keypair();
+        let seed = hash(b"seed");
+
+        for height in 1..4 {
+            let sender = Box::new(sent.clone());
+            worker.request(seed, DIFFICULTY, VdfBackendKind::Mimc, keys.clone(), sender, Height(height));
+        }
+        assert_eq!(sent.wait_for(1), vec![expected_tx(&seed, &keys)]);
+
+        // Finished evaluation isn't repeated
+        worker.request(seed, DIFFICULTY, VdfBackendKind::Mimc, keys.clone(), Box::new(sent.clone()), Height(4));
+        thread::sleep(Duration::from_millis(100));
+        assert_eq!(sent.wait_for(1).len(), 1);
+    }
+
+    #[test]
+    fn test_stale_seed_is_cancelled() {
+        let worker = VdfWorker::new();
+        let sent = SentTransactions::default();
+        let keys = gen_keypair();
+        let (stale, seed) = (hash(b"stale seed"), hash(b"seed"));
+
+        // Evaluation of the stale seed would take practically forever
+        worker.request(
+            stale,
+            1 << 40,
+            VdfBackendKind::Wesolowski,
+            keys.clone(),
+            Box::new(sent.clone()),
+            Height(1),
+        );
+        worker.request(seed, DIFFICULTY, VdfBackendKind::Mimc, keys.clone(), Box::new(sent.clone()), Height(2));
+        assert_eq!(sent.wait_for(1), vec![expected_tx(&seed, &keys)]);
+
+        // Stale seed is cancelled as well when the round moves on
+        worker.request(
+            stale,
+            1 << 40,
+            VdfBackendKind::Wesolowski,
+            keys.clone(),
+            Box::new(sent.clone()),
+            Height(3),
+        );
+        worker.cancel_stale(None);
+        assert!(worker.active.lock().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_result_is_published_again() {
+        let worker = VdfWorker::new().with_retry_interval(2);
+        let sent = SentTransactions::default();
+        let keys = gen_keypair();
+        let seed = hash(b"seed");
+
+        worker.request(seed, DIFFICULTY, VdfBackendKind::Mimc, keys.clone(), Box::new(sent.clone()), Height(1));
+        let tx_hash = expected_tx(&seed, &keys);
+        assert_eq!(sent.wait_for(1), vec![tx_hash]);
+
+        // Result isn't accepted for `retry_interval` blocks since it's been noticed as published
+        for height in 2..4 {
+            let sender = Box::new(sent.clone());
+            worker.request(seed, DIFFICULTY, VdfBackendKind::Mimc, keys.clone(), sender, Height(height));
+        }
+        assert_eq!(sent.wait_for(1).len(), 1);
+
+        worker.request(seed, DIFFICULTY, VdfBackendKind::Mimc, keys.clone(), Box::new(sent.clone()), Height(4));
+        assert_eq!(sent.wait_for(2), vec![tx_hash, tx_hash]);
+    }
+}
@@ -1,39 +1,128 @@
+extern crate actix_web;
 extern crate exonum;
 extern crate exonum_rng;
+#[macro_use]
 extern crate exonum_testkit;
-extern crate exonum_configuration;
-//#[macro_use]
-//extern crate serde_json;
+#[macro_use]
+extern crate pretty_assertions;
+extern crate reqwest;
+extern crate serde_json;
+
+use actix_web::{test::TestServer, App};
+use exonum::{
+    api::{self, ApiAccess, ApiAggregator, ServiceApiState},
+    blockchain::{Schema as CoreSchema, Transaction, TransactionErrorType},
+    crypto::{self, Hash, PublicKey},
+    helpers::Height,
+    messages::Message,
+    node::SharedNodeState,
+    storage::Snapshot,
+};
+use exonum_testkit::{ApiKind, TestKit, TestKitApi, TestKitBuilder};
+use exonum_rng::{
+    backend::MimcVdf,
+    api::{
+        EquivocationsQuery, RequestQuery, RoundQuery, RoundStatus, RoundsRangeQuery,
+        ValidatorParticipation, ValidatorStatsQuery, METRICS_CONTENT_TYPE,
+    },
+    beacon::{Beacon, RoundOutput},
+    blockchain::{
+        bigint::BigInt,
+        config::{ContributionSource, RngConfig, DEFAULT_COMMIT_TIMEOUT, DEFAULT_VDF_DIFFICULTY},
+        errors::Error,
+        proof::{verify_round_proof, ProofError, RoundProof},
+        schema::{
+            Equivocation, RandomnessRequest, RngSchema, RoundInfo, ValidatorStats, MAX_PENDING_REQUESTS,
+            MAX_REQUESTS_PER_ROUND, MAX_REQUEST_HORIZON,
+        },
+        transactions::{TxPublishSeedCommitment, TxPublishVdfResult, TxRequestRandomness, TxRevealSeed},
+    },
+    export::{rounds_to_csv, rounds_to_json, CSV_HEADER},
+    rng::{calculate_seed_commitment, calculate_vdf},
+    verify::{verify_round, Check, VerificationError, Verdict},
+    ExonumRngService, SERVICE_NAME,
+};
 
-/*
 struct ExonumRngApi {
     pub inner: TestKitApi,
 }
 
 impl ExonumRngApi {
-    fn assert_tx_status(&self, tx_hash: Hash, expected_status: &serde_json::Value) {
-        let info: serde_json::Value = self.inner
-            .public(ApiKind::Explorer)
-            .query(&TransactionQuery::new(tx_hash))
-            .get("v1/transactions")
-            .unwrap();
-
-        if let serde_json::Value::Object(mut info) = info {
-            let tx_status = info.remove("status").unwrap();
-            assert_eq!(tx_status, *expected_status);
-        } else {
-            panic!("Invalid transaction info format, object expected");
-        }
+    fn current_round(&self) -> RoundStatus {
+        self.inner
+            .public(ApiKind::Service(SERVICE_NAME))
+            .get("v1/round/current")
+            .unwrap()
+    }
+
+    fn latest_randomness(&self) -> api::Result<RoundInfo> {
+        self.inner
+            .public(ApiKind::Service(SERVICE_NAME))
+            .get("v1/randomness/latest")
+    }
+
+    fn round(&self, round: u64) -> api::Result<RoundInfo> {
+        self.inner
+            .public(ApiKind::Service(SERVICE_NAME))
+            .query(&RoundQuery { round })
+            .get("v1/rounds/round")
+    }
+
+    fn rounds(&self, from: u64, count: u64) -> api::Result<Vec<RoundInfo>> {
+        self.inner
+            .public(ApiKind::Service(SERVICE_NAME))
+            .query(&RoundsRangeQuery { from, count })
+            .get("v1/rounds")
+    }
+
+    fn request(&self, id: &Hash) -> api::Result<RandomnessRequest> {
+        self.inner
+            .public(ApiKind::Service(SERVICE_NAME))
+            .query(&RequestQuery { id: *id })
+            .get("v1/requests/request")
     }
 
-    fn assert_tx_success(&self, tx_hash: Hash) {
-        self.assert_tx_status(tx_hash, &json!({ "type": "success" }));
+    fn equivocations(&self, pub_key: Option<PublicKey>) -> api::Result<Vec<Equivocation>> {
+        self.inner
+            .public(ApiKind::Service(SERVICE_NAME))
+            .query(&EquivocationsQuery { pub_key })
+            .get("v1/equivocations")
+    }
+
+    fn validator_stats(&self, pub_key: Option<PublicKey>) -> api::Result<Vec<ValidatorParticipation>> {
+        self.inner
+            .public(ApiKind::Service(SERVICE_NAME))
+            .query(&ValidatorStatsQuery { pub_key })
+            .get("v1/validators/stats")
+    }
+
+    fn round_proof(&self, round: u64) -> RoundProof {
+        self.inner
+            .public(ApiKind::Service(SERVICE_NAME))
+            .query(&RoundQuery { round })
+            .get("v1/rounds/proof")
+            .unwrap()
     }
 }
 
+fn assert_tx_error<T: AsRef<Snapshot>>(snapshot: T, tx_hash: &Hash, expected_error: Error) {
+    let result = CoreSchema::new(snapshot)
+        .transaction_results()
+        .get(tx_hash)
+        .expect("Transaction is not committed");
+
+    let error = result.0.expect_err("Transaction is expected to fail");
+    assert_eq!(error.error_type(), TransactionErrorType::Code(expected_error as u8));
+}
+
 fn create_testkit() -> (TestKit, ExonumRngApi) {
+    create_testkit_with_config(RngConfig::default())
+}
+
+fn create_testkit_with_config(config: RngConfig) -> (TestKit, ExonumRngApi) {
     let testkit = TestKitBuilder::validator()
-        .with_service(ExonumRngService)
+        .with_validators(4)
+        .with_service(ExonumRngService::with_config(config))
         .create();
 
     let api = ExonumRngApi {
@@ -41,4 +130,739 @@ fn create_testkit() -> (TestKit, ExonumRngApi) {
     };
     (testkit, api)
 }
-*/
+
+/// Publishes seed commitments, reveals and VDF results from the first three validators.
+fn finalize_round(testkit: &mut TestKit) {
+    let validators = testkit.network().validators()[..3].to_vec();
+    let round = RngSchema::new(testkit.snapshot()).current_round();
+    let salt = Hash::zero();
+
+    for (i, validator) in validators.iter().enumerate() {
+        let (pub_key, sec_key) = validator.service_keypair();
+        let commitment = calculate_seed_commitment(&BigInt::from(i as u64), &salt);
+        let tx = TxPublishSeedCommitment::new(pub_key, round, &commitment, sec_key);
+        testkit.create_block_with_transactions(txvec![tx]);
+    }
+
+    for (i, validator) in validators.iter().enumerate() {
+        let (pub_key, sec_key) = validator.service_keypair();
+        let value = BigInt::from(i as u64).to_bytes();
+        let tx = TxRevealSeed::new(pub_key, round, &value, &salt, sec_key);
+        testkit.create_block_with_transactions(txvec![tx]);
+    }
+
+    let seed = RngSchema::new(testkit.snapshot()).last_seed().get().unwrap();
+    let value = calculate_vdf(&MimcVdf, &seed, DEFAULT_VDF_DIFFICULTY)
+        .unwrap()
+        .encoded_value();
+    for validator in &validators {
+        let (pub_key, sec_key) = validator.service_keypair();
+        let tx = TxPublishVdfResult::new(pub_key, &seed, &value, &[], sec_key);
+        testkit.create_block_with_transactions(txvec![tx]);
+    }
+}
+
+#[test]
+fn test_current_round_status() {
+    let (mut testkit, api) = create_testkit();
+
+    assert_eq!(
+        api.current_round(),
+        RoundStatus {
+            round: 0,
+            seed: None,
+            difficulty: DEFAULT_VDF_DIFFICULTY,
+            seed_commitments: vec![],
+            vdf_results: vec![],
+            threshold: 3,
+            deadline: Height(DEFAULT_COMMIT_TIMEOUT),
+        }
+    );
+
+    let validator = testkit.network().validators()[0].clone();
+    let tx = {
+        let (pub_key, sec_key) = validator.service_keypair();
+        TxPublishSeedCommitment::new(pub_key, 0, &Hash::zero(), sec_key)
+    };
+    testkit.create_block_with_transactions(txvec![tx]);
+
+    let status = api.current_round();
+    assert_eq!(status.seed_commitments, vec![*validator.service_keypair().0]);
+    assert!(status.vdf_results.is_empty());
+    assert_eq!(status.seed, None);
+}
+
+#[test]
+fn test_rounds_history_is_empty() {
+    let (_testkit, api) = create_testkit();
+
+    assert!(api.latest_randomness().is_err());
+    assert!(api.round(0).is_err());
+    assert_eq!(api.rounds(0, 10).unwrap(), vec![]);
+    assert!(api.rounds(0, 1000).is_err());
+}
+
+#[test]
+fn test_round_proof() {
+    let (mut testkit, api) = create_testkit();
+    finalize_round(&mut testkit);
+
+    let expected = api.round(0).unwrap();
+    assert_eq!(api.latest_randomness().unwrap(), expected);
+    assert_eq!(api.rounds(0, 10).unwrap(), vec![expected.clone()]);
+
+    let consensus_keys = testkit
+        .network()
+        .validators()
+        .iter()
+        .map(|validator| validator.public_keys().consensus_key)
+        .collect::<Vec<_>>();
+
+    let proof = api.round_proof(0);
+    assert_eq!(verify_round_proof(&proof, &consensus_keys, 0), Ok(expected));
+
+    // Proof doesn't hold for unrelated keys
+    let service_keys = testkit
+        .network()
+        .validators()
+        .iter()
+        .map(|validator| validator.public_keys().service_key)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        verify_round_proof(&proof, &service_keys, 0),
+        Err(ProofError::InvalidPrecommitSignature)
+    );
+}
+
+#[test]
+fn test_latest_randomness_skips_failed_rounds() {
+    let (mut testkit, api) = create_testkit_with_config(RngConfig {
+        commit_timeout: 20,
+        ..Default::default()
+    });
+    finalize_round(&mut testkit);
+    let finalized = api.round(0).unwrap();
+
+    // Nobody participates in the next round until it's aborted
+    let deadline = api.current_round().deadline;
+    while testkit.height() < deadline {
+        testkit.create_block_with_transactions(txvec![]);
+    }
+
+    let failed = api.round(1).unwrap();
+    assert!(failed.failed());
+    assert_eq!(api.latest_randomness().unwrap(), finalized);
+    assert_eq!(api.current_round().round, 2);
+}
+
+#[test]
+fn test_randomness_requests() {
+    let (mut testkit, api) = create_testkit();
+
+    let (pub_key, sec_key) = crypto::gen_keypair();
+    let tag = crypto::hash(b"lottery");
+    let first = TxRequestRandomness::new(&pub_key, 0, &tag, &sec_key);
+    let second = TxRequestRandomness::new(&pub_key, 1, &tag, &sec_key);
+    testkit.create_block_with_transactions(txvec![first.clone(), second.clone()]);
+
+    let request = api.request(&first.hash()).unwrap();
+    assert_eq!(request.requester(), &pub_key);
+    assert_eq!(request.round(), 0);
+    assert!(!request.fulfilled());
+    assert!(api.request(&Hash::zero()).is_err());
+
+    finalize_round(&mut testkit);
+    let info = api.round(0).unwrap();
+    let output = RoundOutput {
+        round: 0,
+        seed: *info.seed(),
+        randomness: *info.randomness(),
+        height: info.height(),
+    };
+
+    // Request of the finalized round is fulfilled, the other one is pending
+    let request = api.request(&first.hash()).unwrap();
+    assert!(request.fulfilled());
+    assert_eq!(request.fulfilled_round(), 0);
+    assert_eq!(request.randomness(), info.randomness());
+    assert!(!api.request(&second.hash()).unwrap().fulfilled());
+
+    let snapshot = testkit.snapshot();
+    let beacon = Beacon::new(&snapshot);
+    assert_eq!(beacon.current_round(), 1);
+    assert_eq!(beacon.output(0), Some(output.clone()));
+    assert_eq!(beacon.output(1), None);
+    assert_eq!(beacon.output_at_or_after(0), Some(output.clone()));
+    assert_eq!(beacon.output_at_or_after(1), None);
+    assert_eq!(beacon.latest_output(), Some(output));
+    assert_eq!(beacon.request(&first.hash()), Some(request));
+
+    // Request of the round that is over is rejected
+    let late = TxRequestRandomness::new(&pub_key, 0, &crypto::hash(b"late"), &sec_key);
+    testkit.create_block_with_transactions(txvec![late.clone()]);
+    assert!(api.request(&late.hash()).is_err());
+}
+
+#[test]
+fn test_pending_requests_are_carried_over_and_limited() {
+    let (mut testkit, api) = create_testkit();
+    let (pub_key, sec_key) = crypto::gen_keypair();
+    let requests = (0..MAX_PENDING_REQUESTS)
+        .map(|i| {
+            let tag = crypto::hash(format!("request {}", i).as_bytes());
+            TxRequestRandomness::new(&pub_key, 0, &tag, &sec_key)
+        })
+        .collect::<Vec<_>>();
+    for tx in &requests {
+        testkit.create_block_with_transactions(txvec![tx.clone()]);
+    }
+
+    // Requester can't have more pending requests, even for other rounds
+    let extra = TxRequestRandomness::new(&pub_key, 1, &crypto::hash(b"extra"), &sec_key);
+    testkit.create_block_with_transactions(txvec![extra.clone()]);
+    assert!(api.request(&extra.hash()).is_err());
+    let result = CoreSchema::new(testkit.snapshot())
+        .transaction_results()
+        .get(&extra.hash())
+        .unwrap();
+    assert_eq!(
+        result.0.unwrap_err().error_type(),
+        TransactionErrorType::Code(Error::TooManyRequests as u8)
+    );
+
+    // Requests of the aborted round are carried over to the next one
+    let deadline = api.current_round().deadline;
+    while testkit.height() < deadline {
+        testkit.create_block_with_transactions(txvec![]);
+    }
+    {
+        let schema = RngSchema::new(testkit.snapshot());
+        assert!(schema.round_info(0).unwrap().failed());
+        assert_eq!(schema.pending_requests_of_round(0).len(), 0);
+        assert_eq!(schema.pending_requests_of_round(1).len(), MAX_PENDING_REQUESTS);
+        assert_eq!(schema.pending_requests_count().get(&pub_key), Some(MAX_PENDING_REQUESTS));
+    }
+
+    finalize_round(&mut testkit);
+    for tx in &requests {
+        let request = api.request(&tx.hash()).unwrap();
+        assert!(request.fulfilled());
+        assert_eq!(request.fulfilled_round(), 1);
+    }
+    {
+        let schema = RngSchema::new(testkit.snapshot());
+        assert_eq!(schema.pending_requests_of_round(1).len(), 0);
+        assert_eq!(schema.pending_requests().iter().count(), 0);
+        assert_eq!(schema.pending_requests_count().get(&pub_key), None);
+    }
+
+    // Fulfilled requests don't count towards the limit
+    let next = TxRequestRandomness::new(&pub_key, 2, &crypto::hash(b"next"), &sec_key);
+    testkit.create_block_with_transactions(txvec![next.clone()]);
+    assert!(!api.request(&next.hash()).unwrap().fulfilled());
+}
+
+#[test]
+fn test_requests_are_limited_per_round_and_horizon() {
+    let (mut testkit, api) = create_testkit();
+
+    // Requests beyond the horizon are refused
+    let (pub_key, sec_key) = crypto::gen_keypair();
+    let far = TxRequestRandomness::new(&pub_key, MAX_REQUEST_HORIZON + 1, &Hash::zero(), &sec_key);
+    let last = TxRequestRandomness::new(&pub_key, MAX_REQUEST_HORIZON, &Hash::zero(), &sec_key);
+    testkit.create_block_with_transactions(txvec![far.clone(), last.clone()]);
+    assert_tx_error(testkit.snapshot(), &far.hash(), Error::RoundTooFar);
+    assert!(api.request(&last.hash()).is_ok());
+
+    // Using new keys doesn't bypass the limit of the round
+    let requests = (0..MAX_REQUESTS_PER_ROUND)
+        .map(|i| {
+            let (pub_key, sec_key) = crypto::gen_keypair();
+            let tag = crypto::hash(format!("request {}", i).as_bytes());
+            Box::new(TxRequestRandomness::new(&pub_key, 1, &tag, &sec_key)) as Box<Transaction>
+        })
+        .collect::<Vec<_>>();
+    testkit.create_block_with_transactions(requests);
+    assert_eq!(
+        RngSchema::new(testkit.snapshot()).pending_requests_of_round(1).len(),
+        MAX_REQUESTS_PER_ROUND
+    );
+
+    let (pub_key, sec_key) = crypto::gen_keypair();
+    let extra = TxRequestRandomness::new(&pub_key, 1, &Hash::zero(), &sec_key);
+    let other = TxRequestRandomness::new(&pub_key, 2, &Hash::zero(), &sec_key);
+    testkit.create_block_with_transactions(txvec![extra.clone(), other.clone()]);
+    assert_tx_error(testkit.snapshot(), &extra.hash(), Error::RoundIsFull);
+    assert!(api.request(&other.hash()).is_ok());
+}
+
+/// Returns copy of the round with the given fields replaced.
+fn tamper_round(
+    info: &RoundInfo,
+    randomness: &Hash,
+    validators: Vec<PublicKey>,
+    vdf_submitters: Vec<PublicKey>,
+    contribution_source: ContributionSource,
+) -> RoundInfo {
+    RoundInfo::new(
+        info.round(),
+        info.seed(),
+        info.commitments(),
+        info.vdf_output(),
+        info.vdf_proof(),
+        info.difficulty(),
+        info.vdf_backend(),
+        info.height(),
+        randomness,
+        info.disagreeing_validators(),
+        info.failed(),
+        info.missing_validators(),
+        validators,
+        vdf_submitters,
+        contribution_source.id(),
+    )
+}
+
+#[test]
+fn test_verify_round() {
+    let (mut testkit, api) = create_testkit();
+    finalize_round(&mut testkit);
+
+    let service_keys = testkit
+        .network()
+        .validators()
+        .iter()
+        .map(|validator| validator.public_keys().service_key)
+        .collect::<Vec<_>>();
+
+    let info = api.round(0).unwrap();
+    assert_eq!(info.validators(), service_keys);
+    let mut submitters = info.vdf_submitters();
+    submitters.sort();
+    let mut expected_submitters = service_keys[..3].to_vec();
+    expected_submitters.sort();
+    assert_eq!(submitters, expected_submitters);
+
+    assert_eq!(info.contribution_source(), ContributionSource::Os.id());
+    let verdict = verify_round(&info, &service_keys, ContributionSource::Os);
+    assert!(verdict.is_valid());
+    assert_eq!(verdict.passed.last(), Some(&Check::Randomness));
+
+    // Contributions in the test are not signatures
+    let tampered = tamper_round(
+        &info,
+        info.randomness(),
+        info.validators(),
+        info.vdf_submitters(),
+        ContributionSource::Signature,
+    );
+    let verdict = verify_round(&tampered, &service_keys, ContributionSource::Signature);
+    assert_eq!(verdict.passed, vec![Check::Finalized, Check::Validators, Check::Quorum, Check::Commitments]);
+    assert_eq!(
+        verdict.failed,
+        Some((
+            Check::Signatures,
+            VerificationError::InvalidContributionSignature(*info.commitments()[0].pub_key())
+        ))
+    );
+
+    // Round can't bring its own contribution source
+    let verdict = verify_round(&info, &service_keys, ContributionSource::Signature);
+    assert_eq!(
+        verdict.failed,
+        Some((
+            Check::Signatures,
+            VerificationError::UnexpectedContributionSource {
+                expected: ContributionSource::Signature.id(),
+                actual: ContributionSource::Os.id(),
+            }
+        ))
+    );
+
+    // Round can't bring its own validator set
+    let tampered = tamper_round(
+        &info,
+        info.randomness(),
+        service_keys[1..].to_vec(),
+        info.vdf_submitters(),
+        ContributionSource::Os,
+    );
+    let verdict = verify_round(&tampered, &service_keys, ContributionSource::Os);
+    assert_eq!(verdict.failed, Some((Check::Validators, VerificationError::UnexpectedValidatorSet)));
+    let verdict = verify_round(&info, &service_keys[1..], ContributionSource::Os);
+    assert_eq!(verdict.failed, Some((Check::Validators, VerificationError::UnexpectedValidatorSet)));
+
+    // Order of the expected validators doesn't matter
+    let mut reversed = service_keys.clone();
+    reversed.reverse();
+    assert!(verify_round(&info, &reversed, ContributionSource::Os).is_valid());
+
+    // VDF output isn't agreed on by the quorum
+    let tampered = tamper_round(
+        &info,
+        info.randomness(),
+        info.validators(),
+        submitters[..2].to_vec(),
+        ContributionSource::Os,
+    );
+    let verdict = verify_round(&tampered, &service_keys, ContributionSource::Os);
+    assert_eq!(
+        verdict.failed,
+        Some((
+            Check::VdfQuorum,
+            VerificationError::NotEnoughVdfResults { found: 2, required: 3 }
+        ))
+    );
+
+    let tampered = tamper_round(
+        &info,
+        info.randomness(),
+        info.validators(),
+        vec![submitters[0], submitters[1], submitters[0]],
+        ContributionSource::Os,
+    );
+    let verdict = verify_round(&tampered, &service_keys, ContributionSource::Os);
+    assert_eq!(
+        verdict.failed,
+        Some((Check::VdfQuorum, VerificationError::DuplicateVdfResult(submitters[0])))
+    );
+
+    // Tampered randomness is detected
+    let forged = crypto::hash(b"forged");
+    let tampered = tamper_round(
+        &info,
+        &forged,
+        info.validators(),
+        info.vdf_submitters(),
+        ContributionSource::Os,
+    );
+    let verdict = verify_round(&tampered, &service_keys, ContributionSource::Os);
+    assert_eq!(verdict.passed.last(), Some(&Check::VdfQuorum));
+    assert_eq!(
+        verdict.failed,
+        Some((
+            Check::Randomness,
+            VerificationError::RandomnessMismatch {
+                expected: *info.randomness(),
+                actual: forged,
+            }
+        ))
+    );
+}
+
+#[test]
+fn test_verdict_is_parsed_back() {
+    let (mut testkit, api) = create_testkit();
+    finalize_round(&mut testkit);
+    let info = api.round(0).unwrap();
+    let validators = info.validators();
+
+    let verdict = verify_round(&info, &validators, ContributionSource::Os);
+    let json = serde_json::to_string_pretty(&verdict).unwrap();
+    assert_eq!(serde_json::from_str::<Verdict>(&json).unwrap(), verdict);
+
+    let forged = crypto::hash(b"forged");
+    let tampered = tamper_round(
+        &info,
+        &forged,
+        info.validators(),
+        info.vdf_submitters(),
+        ContributionSource::Os,
+    );
+    let verdict = verify_round(&tampered, &validators, ContributionSource::Os);
+    let json = serde_json::to_string_pretty(&verdict).unwrap();
+    let parsed = serde_json::from_str::<Verdict>(&json).unwrap();
+    assert!(!parsed.is_valid());
+    assert_eq!(parsed, verdict);
+}
+
+#[test]
+fn test_export_rounds() {
+    let (mut testkit, api) = create_testkit();
+    finalize_round(&mut testkit);
+    let deadline = api.current_round().deadline;
+    while testkit.height() < deadline {
+        testkit.create_block_with_transactions(txvec![]);
+    }
+
+    let rounds = api.rounds(0, 2).unwrap();
+    assert_eq!(rounds.len(), 2);
+    assert!(rounds[1].failed());
+
+    let json = rounds_to_json(&rounds).unwrap();
+    assert_eq!(serde_json::from_str::<Vec<RoundInfo>>(&json).unwrap(), rounds);
+
+    let csv = rounds_to_csv(&rounds);
+    let lines = csv.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0], CSV_HEADER);
+    for (line, info) in lines[1..].iter().zip(&rounds) {
+        let fields = line.split(',').collect::<Vec<_>>();
+        assert_eq!(fields.len(), CSV_HEADER.split(',').count());
+        assert_eq!(fields[0], info.round().to_string());
+        assert_eq!(fields[1], info.height().to_string());
+        assert_eq!(fields[2], info.failed().to_string());
+        assert_eq!(fields[3], info.seed().to_hex());
+        assert_eq!(fields[4], info.randomness().to_hex());
+        assert_eq!(fields[7], info.commitments().len().to_string());
+        assert_eq!(fields[8], info.missing_validators().len().to_string());
+    }
+}
+
+#[test]
+fn test_equivocation_evidence() {
+    let (mut testkit, api) = create_testkit();
+    let validators = testkit.network().validators().to_vec();
+    let salt = Hash::zero();
+
+    // Validator replaces its commitment after the others have committed
+    for (i, validator) in validators[..3].iter().enumerate() {
+        let (pub_key, sec_key) = validator.service_keypair();
+        let commitment = calculate_seed_commitment(&BigInt::from(i as u64), &salt);
+        let tx = TxPublishSeedCommitment::new(pub_key, 0, &commitment, sec_key);
+        testkit.create_block_with_transactions(txvec![tx]);
+    }
+
+    let (pub_key, sec_key) = validators[1].service_keypair();
+    let first = TxPublishSeedCommitment::new(
+        pub_key,
+        0,
+        &calculate_seed_commitment(&BigInt::from(1u64), &salt),
+        sec_key,
+    );
+    let second = TxPublishSeedCommitment::new(pub_key, 0, &crypto::hash(b"other"), sec_key);
+    testkit.create_block_with_transactions(txvec![second.clone()]);
+
+    let evidence = api.equivocations(None).unwrap();
+    assert_eq!(evidence.len(), 1);
+    assert_eq!(evidence[0].pub_key(), pub_key);
+    assert_eq!(evidence[0].round(), 0);
+    assert_eq!(crypto::hash(evidence[0].first()), first.hash());
+    assert_eq!(crypto::hash(evidence[0].second()), second.hash());
+    assert_eq!(evidence[0].height(), testkit.height());
+
+    // Commitment isn't replaced
+    let schema = RngSchema::new(testkit.snapshot());
+    assert_eq!(
+        schema.validators_seed_commitments().get(pub_key),
+        Some(*first.commitment())
+    );
+
+    // Conflicting VDF results are recorded as well
+    for (i, validator) in validators[..3].iter().enumerate() {
+        let (pub_key, sec_key) = validator.service_keypair();
+        let value = BigInt::from(i as u64).to_bytes();
+        let tx = TxRevealSeed::new(pub_key, 0, &value, &salt, sec_key);
+        testkit.create_block_with_transactions(txvec![tx]);
+    }
+
+    let seed = RngSchema::new(testkit.snapshot()).last_seed().get().unwrap();
+    let value = calculate_vdf(&MimcVdf, &seed, DEFAULT_VDF_DIFFICULTY)
+        .unwrap()
+        .encoded_value();
+    let (pub_key, sec_key) = validators[2].service_keypair();
+    let first = TxPublishVdfResult::new(pub_key, &seed, &value, &[], sec_key);
+    let second = TxPublishVdfResult::new(pub_key, &seed, &[42], &[], sec_key);
+    testkit.create_block_with_transactions(txvec![first.clone(), second.clone()]);
+
+    let evidence = api.equivocations(Some(*pub_key)).unwrap();
+    assert_eq!(evidence.len(), 1);
+    assert_eq!(evidence[0].round(), 0);
+    assert_eq!(crypto::hash(evidence[0].first()), first.hash());
+    assert_eq!(crypto::hash(evidence[0].second()), second.hash());
+    assert_eq!(api.equivocations(None).unwrap().len(), 2);
+    assert!(api.equivocations(Some(*validators[3].service_keypair().0)).unwrap().is_empty());
+}
+
+#[test]
+fn test_validator_stats() {
+    let (mut testkit, api) = create_testkit();
+    let validators = testkit.network().validators().to_vec();
+    let keys = validators
+        .iter()
+        .map(|validator| *validator.service_keypair().0)
+        .collect::<Vec<_>>();
+    let salt = Hash::zero();
+
+    for (i, validator) in validators[..3].iter().enumerate() {
+        let (pub_key, sec_key) = validator.service_keypair();
+        let commitment = calculate_seed_commitment(&BigInt::from(i as u64), &salt);
+        let tx = TxPublishSeedCommitment::new(pub_key, 0, &commitment, sec_key);
+        testkit.create_block_with_transactions(txvec![tx]);
+    }
+    for (i, validator) in validators[..3].iter().enumerate() {
+        let (pub_key, sec_key) = validator.service_keypair();
+        let value = BigInt::from(i as u64).to_bytes();
+        let tx = TxRevealSeed::new(pub_key, 0, &value, &salt, sec_key);
+        testkit.create_block_with_transactions(txvec![tx]);
+    }
+
+    // Invalid VDF result is counted even though the transaction is refused
+    let seed = RngSchema::new(testkit.snapshot()).last_seed().get().unwrap();
+    let (pub_key, sec_key) = validators[3].service_keypair();
+    let tx = TxPublishVdfResult::new(pub_key, &seed, &[42], &[], sec_key);
+    testkit.create_block_with_transactions(txvec![tx]);
+
+    let value = calculate_vdf(&MimcVdf, &seed, DEFAULT_VDF_DIFFICULTY)
+        .unwrap()
+        .encoded_value();
+    for validator in &validators[..3] {
+        let (pub_key, sec_key) = validator.service_keypair();
+        let tx = TxPublishVdfResult::new(pub_key, &seed, &value, &[], sec_key);
+        testkit.create_block_with_transactions(txvec![tx]);
+    }
+    assert_eq!(RngSchema::new(testkit.snapshot()).current_round(), 1);
+
+    // Commitments are published 1, 2 and 3 blocks after the start of the round,
+    // VDF results are published 2, 3 and 4 blocks after the seed is agreed on
+    let stats = api.validator_stats(None).unwrap();
+    assert_eq!(stats.len(), 4);
+    for (i, pub_key) in keys[..3].iter().enumerate() {
+        let participation = stats.iter().find(|entry| entry.pub_key == *pub_key).unwrap();
+        let i = i as u64;
+        assert_eq!(
+            participation.stats,
+            ValidatorStats::new(1, 1, 1, 0, 0, (i + 1) + (i + 2), 0)
+        );
+        assert_eq!(participation.average_latency, Some(i as f64 + 1.5));
+    }
+
+    // Validator hasn't committed before the commit phase was closed, so it's locked out
+    let missing = api.validator_stats(Some(keys[3])).unwrap();
+    assert_eq!(
+        missing,
+        vec![ValidatorParticipation {
+            pub_key: keys[3],
+            stats: ValidatorStats::new(0, 0, 0, 1, 0, 0, 1),
+            average_latency: None,
+        }]
+    );
+}
+
+#[test]
+fn test_validator_stats_distinguish_locked_out_validators() {
+    let (mut testkit, api) = create_testkit_with_config(RngConfig {
+        commit_timeout: 20,
+        ..Default::default()
+    });
+    let validators = testkit.network().validators().to_vec();
+    let keys = validators
+        .iter()
+        .map(|validator| *validator.service_keypair().0)
+        .collect::<Vec<_>>();
+    let salt = Hash::zero();
+    let commit = |testkit: &mut TestKit, i: usize, round: u64| {
+        let (pub_key, sec_key) = validators[i].service_keypair();
+        let commitment = calculate_seed_commitment(&BigInt::from(i as u64), &salt);
+        let tx = TxPublishSeedCommitment::new(pub_key, round, &commitment, sec_key);
+        testkit.create_block_with_transactions(txvec![tx]);
+    };
+    let reveal = |testkit: &mut TestKit, i: usize, round: u64| {
+        let (pub_key, sec_key) = validators[i].service_keypair();
+        let value = BigInt::from(i as u64).to_bytes();
+        let tx = TxRevealSeed::new(pub_key, round, &value, &salt, sec_key);
+        testkit.create_block_with_transactions(txvec![tx]);
+    };
+
+    // Validator 3 commits after the quorum has closed the commit phase
+    for i in 0..4 {
+        commit(&mut testkit, i, 0);
+    }
+    for i in 0..3 {
+        reveal(&mut testkit, i, 0);
+    }
+    let seed = RngSchema::new(testkit.snapshot()).last_seed().get().unwrap();
+    let value = calculate_vdf(&MimcVdf, &seed, DEFAULT_VDF_DIFFICULTY)
+        .unwrap()
+        .encoded_value();
+    for validator in &validators[..3] {
+        let (pub_key, sec_key) = validator.service_keypair();
+        let tx = TxPublishVdfResult::new(pub_key, &seed, &value, &[], sec_key);
+        testkit.create_block_with_transactions(txvec![tx]);
+    }
+    assert_eq!(RngSchema::new(testkit.snapshot()).current_round(), 1);
+
+    let stats = api.validator_stats(Some(keys[3])).unwrap();
+    assert_eq!(stats[0].stats.rounds_missed(), 0);
+    assert_eq!(stats[0].stats.rounds_locked_out(), 1);
+
+    // Validator 2 commits, but doesn't reveal, so the round fails
+    for i in 0..3 {
+        commit(&mut testkit, i, 1);
+    }
+    for i in 0..2 {
+        reveal(&mut testkit, i, 1);
+    }
+    let deadline = RngSchema::new(testkit.snapshot()).phase_deadline();
+    while testkit.height() < deadline {
+        testkit.create_block_with_transactions(txvec![]);
+    }
+    assert!(RngSchema::new(testkit.snapshot()).round_info(1).unwrap().failed());
+
+    let stats = api.validator_stats(None).unwrap();
+    let stats_of = |i: usize| stats.iter().find(|entry| entry.pub_key == keys[i]).unwrap().stats.clone();
+    for i in 0..2 {
+        assert_eq!((stats_of(i).rounds_missed(), stats_of(i).rounds_locked_out()), (0, 0));
+    }
+    assert_eq!((stats_of(2).rounds_missed(), stats_of(2).rounds_locked_out()), (1, 0));
+    assert_eq!((stats_of(3).rounds_missed(), stats_of(3).rounds_locked_out()), (0, 2));
+}
+
+#[test]
+fn test_metrics() {
+    let (mut testkit, _api) = create_testkit();
+    let metrics = exonum_rng::metrics::render(testkit.snapshot());
+    assert!(metrics.contains("exonum_rng_round 0\n"));
+    assert!(metrics.contains("exonum_rng_round_phase{phase=\"commit\"} 1\n"));
+    assert!(!metrics.contains("exonum_rng_last_round_duration_blocks"));
+
+    finalize_round(&mut testkit);
+    let info = RngSchema::new(testkit.snapshot()).rounds().get(0).unwrap();
+
+    // Commitment to the finished round is refused
+    let (pub_key, sec_key) = testkit.network().validators()[0].service_keypair();
+    let tx = TxPublishSeedCommitment::new(pub_key, 0, &Hash::zero(), sec_key);
+    testkit.create_block_with_transactions(txvec![tx]);
+
+    let metrics = exonum_rng::metrics::render(testkit.snapshot());
+    assert!(metrics.contains("exonum_rng_round 1\n"));
+    assert!(metrics.contains("exonum_rng_round_phase{phase=\"commit\"} 1\n"));
+    assert!(metrics.contains("exonum_rng_round_phase{phase=\"vdf\"} 0\n"));
+    assert!(metrics.contains(&format!(
+        "exonum_rng_last_round_duration_blocks {}\n",
+        info.height().0
+    )));
+    assert!(metrics.contains("exonum_rng_last_round_commitments 3\n"));
+    assert!(metrics.contains("# TYPE exonum_rng_vdf_verification_seconds summary\n"));
+    // Counters are shared by the tests running in parallel, so only their presence is checked
+    assert!(metrics.contains("exonum_rng_rejected_submissions_total{reason=\"wrong_round\"} "));
+}
+
+#[test]
+fn test_metrics_endpoint() {
+    let (mut testkit, _api) = create_testkit();
+    finalize_round(&mut testkit);
+
+    // Test kit API parses every response as JSON, so the private API is served
+    // by a separate test server the same way
+    let blockchain = testkit.blockchain_mut().clone();
+    let server = TestServer::with_factory(move || {
+        let aggregator = ApiAggregator::new(blockchain.clone(), SharedNodeState::new(10_000));
+        App::with_state(ServiceApiState::new(blockchain.clone()))
+            .scope("public/api", |scope| aggregator.extend_backend(ApiAccess::Public, scope))
+            .scope("private/api", |scope| aggregator.extend_backend(ApiAccess::Private, scope))
+    });
+
+    let url = server.url(&format!("private/api/services/{}/v1/metrics", SERVICE_NAME));
+    let mut response = reqwest::get(&url).unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+    assert_eq!(response.headers()[reqwest::header::CONTENT_TYPE], METRICS_CONTENT_TYPE);
+
+    let body = response.text().unwrap();
+    assert!(body.contains("exonum_rng_round 1\n"));
+    assert!(body.contains("exonum_rng_last_round_commitments 3\n"));
+
+    // Endpoint isn't exposed in the public API
+    let url = server.url(&format!("public/api/services/{}/v1/metrics", SERVICE_NAME));
+    assert_eq!(reqwest::get(&url).unwrap().status(), reqwest::StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,181 @@
+extern crate exonum;
+extern crate exonum_rng;
+#[macro_use]
+extern crate pretty_assertions;
+extern crate rug;
+extern crate tempdir;
+
+use exonum::crypto::hash;
+use exonum_rng::{
+    backend::{VdfBackend, VdfBackendKind, VdfOutput},
+    blockchain::bigint::{BigInt, MAX_BIG_INT_BYTES},
+    rng::{calculate_vdf, calculate_vdf_resumable, validate_vdf, CheckpointFile, VdfCheckpoint},
+};
+use rug::Integer;
+use tempdir::TempDir;
+
+use std::sync::atomic::AtomicBool;
+
+const DIFFICULTY: u64 = 1000;
+
+/// Odd difficulty at which proofs are assembled from many saved powers of the input.
+const LARGE_DIFFICULTY: u64 = 100_003;
+
+fn assert_backend_roundtrip(kind: VdfBackendKind) {
+    let backend = kind.backend();
+    assert_eq!(backend.parameters().kind, kind);
+
+    let seed = hash(b"seed");
+    let output = calculate_vdf(&*backend, &seed, DIFFICULTY).unwrap();
+    assert_eq!(output.proof.len(), backend.proof_size(DIFFICULTY));
+    assert!(validate_vdf(&*backend, &seed, &output, DIFFICULTY));
+
+    // Output survives encoding into the transaction
+    let decoded = VdfOutput::decode(&output.encoded_value(), &output.encoded_proof()).unwrap();
+    assert_eq!(decoded, output);
+
+    // Output doesn't hold for other seed or difficulty
+    assert!(!validate_vdf(&*backend, &hash(b"other seed"), &output, DIFFICULTY));
+    assert!(!validate_vdf(&*backend, &seed, &output, DIFFICULTY + 1));
+
+    // Tampered value is rejected
+    let mut tampered = output.clone();
+    tampered.value += 1;
+    assert!(!validate_vdf(&*backend, &seed, &tampered, DIFFICULTY));
+}
+
+#[test]
+fn test_mimc_backend() {
+    assert_backend_roundtrip(VdfBackendKind::Mimc);
+}
+
+#[test]
+fn test_wesolowski_backend() {
+    assert_backend_roundtrip(VdfBackendKind::Wesolowski);
+}
+
+#[test]
+fn test_pietrzak_backend() {
+    assert_backend_roundtrip(VdfBackendKind::Pietrzak);
+
+    let backend = VdfBackendKind::Pietrzak.backend();
+    let seed = hash(b"seed");
+    let mut output = calculate_vdf(&*backend, &seed, DIFFICULTY).unwrap();
+
+    // Tampered proof is rejected
+    output.proof[0] += 1;
+    assert!(!validate_vdf(&*backend, &seed, &output, DIFFICULTY));
+
+    // Truncated proof is rejected
+    output.proof.pop();
+    assert!(!validate_vdf(&*backend, &seed, &output, DIFFICULTY));
+}
+
+fn assert_large_difficulty_proof(kind: VdfBackendKind) {
+    let backend = kind.backend();
+    let seed = hash(b"seed");
+    let output = calculate_vdf(&*backend, &seed, LARGE_DIFFICULTY).unwrap();
+    assert!(validate_vdf(&*backend, &seed, &output, LARGE_DIFFICULTY));
+
+    // Powers saved before the checkpoint are recovered, so the proof is the same
+    let dir = TempDir::new("exonum_rng").unwrap();
+    let checkpoints = CheckpointFile::new(dir.path().join("vdf.checkpoint")).with_interval(30_000);
+    let not_cancelled = AtomicBool::new(false);
+    calculate_vdf_resumable(&*backend, &seed, 50_000, &not_cancelled, &checkpoints).unwrap();
+    let resumed = calculate_vdf_resumable(&*backend, &seed, LARGE_DIFFICULTY, &not_cancelled, &checkpoints).unwrap();
+    assert_eq!(resumed, output);
+}
+
+#[test]
+fn test_wesolowski_large_difficulty() {
+    assert_large_difficulty_proof(VdfBackendKind::Wesolowski);
+}
+
+#[test]
+fn test_pietrzak_large_difficulty() {
+    assert_large_difficulty_proof(VdfBackendKind::Pietrzak);
+}
+
+#[test]
+fn test_malformed_outputs() {
+    // Leading zero bytes
+    assert_eq!(VdfOutput::decode(&[0, 42], &[]), None);
+    assert_eq!(VdfOutput::decode(&[42], &[0, 0, 0, 2, 0, 1]), None);
+    // Truncated proof element
+    assert_eq!(VdfOutput::decode(&[42], &[0, 0, 0, 2, 1]), None);
+    // Oversized value
+    assert_eq!(VdfOutput::decode(&[1; MAX_BIG_INT_BYTES + 1], &[]), None);
+    assert_eq!(
+        VdfOutput::decode(&[42], &[0, 0, 0, 2, 1, 0]),
+        Some(VdfOutput {
+            value: Integer::from(42),
+            proof: vec![Integer::from(256)],
+        })
+    );
+}
+
+fn assert_backend_resumes(kind: VdfBackendKind) {
+    let backend = kind.backend();
+    let dir = TempDir::new("exonum_rng").unwrap();
+    let checkpoints = CheckpointFile::new(dir.path().join("vdf.checkpoint")).with_interval(100);
+    let not_cancelled = AtomicBool::new(false);
+    let seed = hash(b"seed");
+
+    // Evaluation is interrupted after 250 squarings, the latest checkpoint is saved
+    let partial = calculate_vdf_resumable(&*backend, &seed, 250, &not_cancelled, &checkpoints).unwrap();
+    assert_eq!(partial, calculate_vdf(&*backend, &seed, 250).unwrap());
+    let checkpoint = checkpoints.load().unwrap();
+    assert_eq!((checkpoint.seed, checkpoint.backend, checkpoint.iterations), (seed, kind, 250));
+
+    // Evaluation resumes from the checkpoint and gets the same output
+    let expected = calculate_vdf(&*backend, &seed, DIFFICULTY).unwrap();
+    let output = calculate_vdf_resumable(&*backend, &seed, DIFFICULTY, &not_cancelled, &checkpoints).unwrap();
+    assert_eq!(output, expected);
+    assert_eq!(checkpoints.load().unwrap().iterations, DIFFICULTY);
+
+    // Checkpoint of another seed is ignored and overwritten
+    let other_seed = hash(b"other seed");
+    let output = calculate_vdf_resumable(&*backend, &other_seed, DIFFICULTY, &not_cancelled, &checkpoints).unwrap();
+    assert_eq!(output, calculate_vdf(&*backend, &other_seed, DIFFICULTY).unwrap());
+    assert_eq!(checkpoints.load().unwrap().seed, other_seed);
+
+    // Corrupted checkpoint is discarded, so the output is still valid
+    checkpoints
+        .store(&VdfCheckpoint {
+            seed,
+            backend: kind,
+            iterations: 500,
+            value: BigInt::from(12_345u64),
+            powers: Vec::new(),
+        })
+        .unwrap();
+    let output = calculate_vdf_resumable(&*backend, &seed, DIFFICULTY, &not_cancelled, &checkpoints).unwrap();
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn test_wesolowski_resumes_from_checkpoint() {
+    assert_backend_resumes(VdfBackendKind::Wesolowski);
+}
+
+#[test]
+fn test_pietrzak_resumes_from_checkpoint() {
+    assert_backend_resumes(VdfBackendKind::Pietrzak);
+}
+
+#[test]
+fn test_mimc_is_not_resumable() {
+    let backend = VdfBackendKind::Mimc.backend();
+    assert!(!backend.is_resumable());
+    assert!(VdfBackendKind::Wesolowski.backend().is_resumable());
+    assert!(VdfBackendKind::Pietrzak.backend().is_resumable());
+
+    // Evaluation still succeeds, but leaves no checkpoint behind
+    let dir = TempDir::new("exonum_rng").unwrap();
+    let checkpoints = CheckpointFile::new(dir.path().join("vdf.checkpoint")).with_interval(100);
+    let seed = hash(b"seed");
+    let output =
+        calculate_vdf_resumable(&*backend, &seed, DIFFICULTY, &AtomicBool::new(false), &checkpoints).unwrap();
+    assert_eq!(output, calculate_vdf(&*backend, &seed, DIFFICULTY).unwrap());
+    assert_eq!(checkpoints.load(), None);
+}
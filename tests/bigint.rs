@@ -0,0 +1,44 @@
+extern crate exonum;
+extern crate exonum_rng;
+#[macro_use]
+extern crate pretty_assertions;
+extern crate serde_json;
+
+use exonum::storage::StorageValue;
+use exonum_rng::blockchain::bigint::{BigInt, BigIntError, MAX_BIG_INT_BYTES};
+
+#[test]
+fn test_big_int_encoding() {
+    let value: BigInt = "258".parse().unwrap();
+    assert_eq!(value, BigInt::from(258u64));
+    assert_eq!(value.to_bytes(), vec![1, 2]);
+    assert_eq!(BigInt::decode(&[1, 2]), Ok(value.clone()));
+    assert_eq!(BigInt::from_bytes(value.clone().into_bytes().into()), value);
+
+    // Zero is encoded as an empty string
+    assert_eq!(BigInt::from(0u64).to_bytes(), Vec::<u8>::new());
+    assert_eq!(BigInt::decode(&[]), Ok(BigInt::from(0u64)));
+
+    // JSON representation is a decimal string
+    assert_eq!(serde_json::to_string(&value).unwrap(), "\"258\"");
+    assert_eq!(serde_json::from_str::<BigInt>("\"258\"").unwrap(), value);
+    assert!(serde_json::from_str::<BigInt>("\"-258\"").is_err());
+}
+
+#[test]
+fn test_malformed_big_ints() {
+    assert_eq!("".parse::<BigInt>(), Err(BigIntError::Malformed));
+    assert_eq!("0x42".parse::<BigInt>(), Err(BigIntError::Malformed));
+    assert_eq!("-42".parse::<BigInt>(), Err(BigIntError::Malformed));
+    assert_eq!(" 42".parse::<BigInt>(), Err(BigIntError::Malformed));
+
+    assert_eq!(BigInt::decode(&[0, 42]), Err(BigIntError::NonCanonical));
+    assert_eq!(
+        BigInt::decode(&[1; MAX_BIG_INT_BYTES + 1]),
+        Err(BigIntError::TooLarge)
+    );
+    assert!(BigInt::decode(&[1; MAX_BIG_INT_BYTES]).is_ok());
+
+    let too_large = "9".repeat(MAX_BIG_INT_BYTES * 3);
+    assert_eq!(too_large.parse::<BigInt>(), Err(BigIntError::TooLarge));
+}
@@ -0,0 +1,114 @@
+extern crate exonum;
+extern crate exonum_rng;
+#[macro_use]
+extern crate pretty_assertions;
+extern crate tempdir;
+extern crate toml;
+
+use exonum::helpers::fabric::NodeBuilder;
+use exonum_rng::{
+    backend::VdfBackendKind,
+    blockchain::config::{ContributionSource, RngConfig},
+    ExonumRngService, SERVICE_NAME,
+};
+use tempdir::TempDir;
+
+use std::{fs, panic, path::Path};
+
+fn run_command(dir: &Path, args: &[&str]) {
+    let mut cmd_line = vec!["exonum-rng".to_owned()];
+    cmd_line.extend(args.iter().map(|arg| {
+        if arg.ends_with(".toml") {
+            dir.join(arg).to_string_lossy().into_owned()
+        } else {
+            (*arg).to_owned()
+        }
+    }));
+
+    NodeBuilder::new()
+        .with_service(Box::new(ExonumRngService::new()))
+        .parse_cmd_string(cmd_line);
+}
+
+fn generate_node_config(dir: &Path, template_args: &[&str]) -> RngConfig {
+    let mut args = vec!["generate-template", "template.toml", "--validators-count", "1"];
+    args.extend_from_slice(template_args);
+    run_command(dir, &args);
+    run_command(
+        dir,
+        &[
+            "generate-config",
+            "template.toml",
+            "pub.toml",
+            "sec.toml",
+            "--peer-address",
+            "127.0.0.1:6333",
+        ],
+    );
+    run_command(
+        dir,
+        &["finalize", "sec.toml", "node.toml", "--public-configs", "pub.toml"],
+    );
+
+    let node_config: toml::Value = fs::read_to_string(dir.join("node.toml"))
+        .unwrap()
+        .parse()
+        .unwrap();
+    node_config["services_configs"][SERVICE_NAME]
+        .clone()
+        .try_into()
+        .unwrap()
+}
+
+#[test]
+fn test_generate_config_with_defaults() {
+    let dir = TempDir::new("exonum_rng").unwrap();
+    let config = generate_node_config(dir.path(), &[]);
+    assert_eq!(config, RngConfig::default());
+}
+
+#[test]
+fn test_generate_config_with_network_parameters() {
+    let dir = TempDir::new("exonum_rng").unwrap();
+    let config = generate_node_config(
+        dir.path(),
+        &[
+            "--rng-vdf-difficulty",
+            "1000",
+            "--rng-vdf-backend",
+            "wesolowski",
+            "--rng-contribution-source",
+            "signature",
+            "--rng-commit-timeout",
+            "10",
+            "--rng-vdf-timeout",
+            "50",
+        ],
+    );
+
+    assert_eq!(
+        config,
+        RngConfig {
+            vdf_difficulty: 1000,
+            vdf_backend: VdfBackendKind::Wesolowski,
+            contribution_source: ContributionSource::Signature,
+            commit_timeout: 10,
+            vdf_timeout: 50,
+        }
+    );
+}
+
+#[test]
+fn test_generate_template_rejects_malformed_parameters() {
+    for &name in &["--rng-vdf-difficulty", "--rng-commit-timeout", "--rng-vdf-timeout"] {
+        let dir = TempDir::new("exonum_rng").unwrap();
+        let result = panic::catch_unwind(|| {
+            run_command(
+                dir.path(),
+                &["generate-template", "template.toml", "--validators-count", "1", name, "ten"],
+            )
+        });
+        assert!(result.is_err(), "{} is accepted", name);
+        assert!(!dir.path().join("template.toml").exists());
+    }
+}
@@ -0,0 +1,124 @@
+extern crate exonum;
+extern crate exonum_rng;
+#[macro_use]
+extern crate pretty_assertions;
+
+use exonum::crypto::{gen_keypair, hash};
+use exonum_rng::{
+    blockchain::bigint::BigInt,
+    rng::{calculate_combined_seed, calculate_randomness, RandomStream},
+};
+
+#[test]
+fn test_combined_seed_is_unambiguous() {
+    let (first, _) = gen_keypair();
+    let (second, _) = gen_keypair();
+
+    // Contributions can't be shifted between validators:
+    // both pairs are concatenated into `[1, 2, 3]`
+    let (a, b) = (BigInt::from(0x0102u64), BigInt::from(0x03u64));
+    let (c, d) = (BigInt::from(0x01u64), BigInt::from(0x0203u64));
+    let seed = calculate_combined_seed(0, &[(first, a.clone()), (second, b.clone())]);
+    let shifted = calculate_combined_seed(0, &[(first, c), (second, d)]);
+    assert_ne!(seed, shifted);
+
+    // Seed depends on the authors of contributions and the round
+    let swapped = calculate_combined_seed(0, &[(first, b.clone()), (second, a.clone())]);
+    assert_ne!(seed, swapped);
+    let next_round = calculate_combined_seed(1, &[(first, a), (second, b)]);
+    assert_ne!(seed, next_round);
+}
+
+#[test]
+fn test_combined_seed_ignores_order_of_contributions() {
+    let contributions = (0..4u64)
+        .map(|i| (gen_keypair().0, BigInt::from(i)))
+        .collect::<Vec<_>>();
+    let mut reversed = contributions.clone();
+    reversed.reverse();
+
+    assert_eq!(
+        calculate_combined_seed(7, &contributions),
+        calculate_combined_seed(7, &reversed)
+    );
+}
+
+#[test]
+fn test_randomness_is_bound_to_round_and_seed() {
+    let (seed, output) = (hash(b"seed"), BigInt::from(42u64));
+    let randomness = calculate_randomness(0, &seed, &output);
+
+    assert_ne!(randomness, calculate_randomness(1, &seed, &output));
+    assert_ne!(randomness, calculate_randomness(0, &hash(b"other seed"), &output));
+    assert_ne!(randomness, calculate_randomness(0, &seed, &BigInt::from(43u64)));
+    // Randomness is not just a hash of the VDF output
+    assert_ne!(randomness, hash(&output.to_bytes()));
+}
+
+#[test]
+fn test_random_stream_known_answer() {
+    // Reference values are calculated with an independent implementation of the spec
+    // documented in `RandomStream`
+    let randomness = hash(b"randomness");
+    let mut stream = RandomStream::new(&randomness, b"lottery", 0);
+    assert_eq!(stream.next_u64(), 0xee10_eed2_e2ec_6420);
+
+    let draws = (0..5).map(|_| stream.gen_range(0, 10)).collect::<Vec<_>>();
+    assert_eq!(draws, vec![6, 4, 5, 8, 9]);
+
+    let mut items = (0..8).collect::<Vec<_>>();
+    stream.shuffle(&mut items);
+    assert_eq!(items, vec![4, 6, 5, 3, 1, 2, 7, 0]);
+
+    let mut stream = RandomStream::new(&randomness, b"lottery", 1);
+    assert_eq!(stream.next_u64(), 0xe133_21e4_90af_076f);
+}
+
+#[test]
+fn test_random_streams_are_independent() {
+    let randomness = hash(b"randomness");
+    let bytes = |label: &[u8], index| RandomStream::new(&randomness, label, index).next_bytes(64);
+
+    assert_eq!(bytes(b"lottery", 0), bytes(b"lottery", 0));
+    assert_ne!(bytes(b"lottery", 0), bytes(b"lottery", 1));
+    assert_ne!(bytes(b"lottery", 0), bytes(b"leaders", 0));
+    assert_ne!(
+        bytes(b"lottery", 0),
+        RandomStream::new(&hash(b"other"), b"lottery", 0).next_bytes(64)
+    );
+
+    // Reading the stream in chunks gives the same bytes
+    let mut stream = RandomStream::new(&randomness, b"lottery", 0);
+    let mut chunked = stream.next_bytes(5);
+    chunked.extend(stream.next_bytes(59));
+    assert_eq!(chunked, bytes(b"lottery", 0));
+}
+
+#[test]
+fn test_random_stream_sampling() {
+    let mut stream = RandomStream::new(&hash(b"randomness"), b"sampling", 0);
+
+    for _ in 0..1000 {
+        let value = stream.gen_range(10, 13);
+        assert!(value >= 10 && value < 13);
+    }
+    assert_eq!(stream.gen_range(7, 8), 7);
+
+    let mut items = (0..100).collect::<Vec<_>>();
+    stream.shuffle(&mut items);
+    let mut sorted = items.clone();
+    sorted.sort();
+    assert_eq!(sorted, (0..100).collect::<Vec<_>>());
+
+    // Items with zero weight are never chosen
+    for _ in 0..100 {
+        let index = stream.choose_weighted(&[0, 5, 0, 1]).unwrap();
+        assert!(index == 1 || index == 3);
+    }
+    assert_eq!(stream.choose_weighted(&[0, 0]), None);
+    assert_eq!(stream.choose_weighted(&[u64::max_value(), 1]), None);
+
+    let mut sample = stream.sample_weighted(&[3, 0, 1, 2], 5);
+    sample.sort();
+    assert_eq!(sample, vec![0, 2, 3]);
+}
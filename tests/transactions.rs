@@ -19,22 +19,37 @@ extern crate exonum_rng;
 #[macro_use]
 extern crate pretty_assertions;
 extern crate rand;
+#[macro_use]
+extern crate serde_json;
+extern crate tempdir;
 
 use rand::{Rng};
+use std::{thread, time::Duration};
+use tempdir::TempDir;
 use exonum::{
-    crypto::Hash,
+    blockchain::{Schema as CoreSchema, Service, TransactionErrorType},
+    crypto::{self, Hash},
+    helpers::Height,
+    messages::Message,
     storage::Snapshot,
 };
 
 use exonum_testkit::{TestKitBuilder, TestNode};
 use exonum_rng::{
-    rng::{calculate_combined_seed, calculate_vdf},
+    backend::MimcVdf,
+    rng::{
+        calculate_combined_seed, calculate_randomness, calculate_seed_commitment, calculate_vdf,
+        random_contribution, signed_contribution, signed_salt, verify_signed_contribution,
+        ContributionFile,
+    },
     blockchain::{
-        schema::{BigInt, RngSchema},
-        transactions::{TxPublishVdfResult, TxPublishSeedCommitment},
-        ToHash,
+        config::{ContributionSource, RngConfig, DEFAULT_VDF_DIFFICULTY},
+        errors::Error,
+        bigint::BigInt,
+        schema::RngSchema,
+        transactions::{TxPublishVdfResult, TxPublishSeedCommitment, TxRevealSeed},
     },
-    ExonumRngService,
+    ExonumRngService, SERVICE_NAME,
 };
 
 fn assert_storage_values_eq<T: AsRef<Snapshot>>(
@@ -42,7 +57,8 @@ fn assert_storage_values_eq<T: AsRef<Snapshot>>(
     validators: &[TestNode],
     expected_seed: Option<Hash>,
     expected_last_randomness: Option<Hash>,
-    expected_validators_seed_commitments: &[Option<BigInt>],
+    expected_validators_seed_commitments: &[Option<Hash>],
+    expected_validators_seed_reveals: &[Option<BigInt>],
     expected_validators_vdf_results: &[Option<BigInt>],
 ) {
     let schema = RngSchema::new(snapshot);
@@ -51,6 +67,7 @@ fn assert_storage_values_eq<T: AsRef<Snapshot>>(
     assert_eq!(schema.last_randomness().get(), expected_last_randomness);
 
     let validators_seed_commitments = schema.validators_seed_commitments();
+    let validators_seed_reveals = schema.validators_seed_reveals();
     let validators_vdf_results = schema.validators_vdf_results();
     for (i, validator) in validators.iter().enumerate() {
         let public_key = &validator.public_keys().service_key;
@@ -60,6 +77,11 @@ fn assert_storage_values_eq<T: AsRef<Snapshot>>(
             expected_validators_seed_commitments[i]
         );
 
+        assert_eq!(
+            validators_seed_reveals.get(public_key).map(|reveal| reveal.contribution()),
+            expected_validators_seed_reveals[i]
+        );
+
         assert_eq!(
             validators_vdf_results.get(public_key),
             expected_validators_vdf_results[i]
@@ -67,6 +89,16 @@ fn assert_storage_values_eq<T: AsRef<Snapshot>>(
     }
 }
 
+fn assert_tx_error<T: AsRef<Snapshot>>(snapshot: T, tx_hash: &Hash, expected_error: Error) {
+    let result = CoreSchema::new(snapshot)
+        .transaction_results()
+        .get(tx_hash)
+        .expect("Transaction is not committed");
+
+    let error = result.0.expect_err("Transaction is expected to fail");
+    assert_eq!(error.error_type(), TransactionErrorType::Code(expected_error as u8));
+}
+
 #[test]
 fn test_exonum_rng_service_with_4_validators() {
     let mut testkit = TestKitBuilder::validator()
@@ -80,10 +112,11 @@ fn test_exonum_rng_service_with_4_validators() {
     let validators = testkit.network().validators().to_vec();
 
     let mut prev_randomness = None;
-    for round_num in 0..3 {
+    for round_num in 0..3u64 {
         // Validators seed commitments, that are saved in storage, look like this:
         // number       | 0    | 1    | 2    | 3    |
         // commitment   | None | None | None | None |
+        // reveal       | None | None | None | None |
         //
         // max_byzantine_nodes = (4 - 1) / 3 = 1.
         //
@@ -97,85 +130,128 @@ fn test_exonum_rng_service_with_4_validators() {
             prev_randomness.clone(),
             &[None, None, None, None],
             &[None, None, None, None],
+            &[None, None, None, None],
+        );
+
+        // Each validator picks its seed contribution `sc` and salt `s`,
+        // and commits to them with `c = hash(sc || s)`
+        let contributions = (0..3)
+            .map(|_| {
+                let value = BigInt::from(rng.gen::<u64>());
+                let salt = Hash::new(rng.gen::<[u8; 32]>());
+                let commitment = calculate_seed_commitment(&value, &salt);
+                (value, salt, commitment)
+            })
+            .collect::<Vec<_>>();
+        let (sc0, sc1, sc2) = (
+            contributions[0].0.clone(),
+            contributions[1].0.clone(),
+            contributions[2].0.clone(),
         );
+        let (c0, c1, c2) = (contributions[0].2, contributions[1].2, contributions[2].2);
 
-        // Add first transaction `tx0` from first validator with seed commitment `sc0`.
-        // After that validators time look like this:
-        // number       | 0       | 1    | 2    | 3    |
-        // commitment   | `sc0`   | None | None | None |
+        // Add commitments from the first two validators.
+        // After that validators seed commitments look like this:
+        // number       | 0       | 1       | 2    | 3    |
+        // commitment   | `c0`    | `c1`    | None | None |
+        // reveal       | None    | None    | None | None |
         //
         // Consolidated seed is: None (not enough commitments)
         // Consolidated randomness is None (no seed yet)
 
-        let sc0 = rng.gen::<u64>().to_string();
-        let tx0 = {
+        for i in 0..2 {
+            let tx = {
+                let (pub_key, sec_key) = validators[i].service_keypair();
+                TxPublishSeedCommitment::new(pub_key, round_num, &contributions[i].2, sec_key)
+            };
+            testkit.create_block_with_transactions(txvec![tx]);
+        }
+
+        // Reveal isn't accepted while the commit phase is in progress
+        let early_reveal = {
             let (pub_key, sec_key) = validators[0].service_keypair();
-            TxPublishSeedCommitment::new(pub_key, &sc0, sec_key)
+            TxRevealSeed::new(pub_key, round_num, &sc0.to_bytes(), &contributions[0].1, sec_key)
         };
-        testkit.create_block_with_transactions(txvec![tx0]);
+        testkit.create_block_with_transactions(txvec![early_reveal.clone()]);
+        assert_tx_error(testkit.snapshot(), &early_reveal.hash(), Error::NotEnoughSeedCommitments);
 
         assert_storage_values_eq(
             testkit.snapshot(),
             &validators,
             None,
             prev_randomness.clone(),
-            &[Some(sc0.clone()), None, None, None],
+            &[Some(c0), Some(c1), None, None],
+            &[None, None, None, None],
             &[None, None, None, None],
         );
 
-        // Add second transaction `tx1` from second validator with another seed commitment.
-        // After that validators time look like this:
-        // number       | 0       | 1       | 2    | 3    |
-        // commitment   | `sc0`   | `sc1`   | None | None |
+        // Add commitment from the third validator, that finishes the commit phase.
+        // After that validators seed commitments look like this:
+        // number       | 0       | 1       | 2       | 3    |
+        // commitment   | `c0`    | `c1`    | `c2`    | None |
+        // reveal       | None    | None    | None    | None |
         //
-        // Consolidated seed is: None (not enough commitments)
+        // Consolidated seed is: None (contributions are not revealed yet)
         // Consolidated randomness is None (no seed yet)
+        let tx2 = {
+            let (pub_key, sec_key) = validators[2].service_keypair();
+            TxPublishSeedCommitment::new(pub_key, round_num, &c2, sec_key)
+        };
+        testkit.create_block_with_transactions(txvec![tx2]);
 
-        let sc1 = rng.gen::<u64>().to_string();
-        let tx1 = {
-            let (pub_key, sec_key) = validators[1].service_keypair();
-            TxPublishSeedCommitment::new(pub_key, &sc1, sec_key)
+        // Reveal that doesn't match the commitment is rejected
+        let wrong_reveal = {
+            let (pub_key, sec_key) = validators[0].service_keypair();
+            TxRevealSeed::new(pub_key, round_num, &sc1.to_bytes(), &contributions[0].1, sec_key)
         };
-        testkit.create_block_with_transactions(txvec![tx1]);
+        testkit.create_block_with_transactions(txvec![wrong_reveal.clone()]);
+        assert_tx_error(testkit.snapshot(), &wrong_reveal.hash(), Error::RevealMismatch);
 
         assert_storage_values_eq(
             testkit.snapshot(),
             &validators,
             None,
             prev_randomness.clone(),
-            &[Some(sc0.clone()), Some(sc1.clone()), None, None],
+            &[Some(c0), Some(c1), Some(c2), None],
+            &[None, None, None, None],
             &[None, None, None, None],
         );
 
-        // Add third transaction `tx2` from third validator
+        // Reveal contributions of all committed validators.
         // After that validators seed commitments look like this:
         // number       | 0       | 1       | 2       | 3    |
-        // commitment   | `sc0`   | `sc1`   | `sc2`   | None |
+        // commitment   | `c0`    | `c1`    | `c2`    | None |
+        // reveal       | `sc0`   | `sc1`   | `sc2`   | None |
         //
-        // Consolidated seed is: `hash(sc0 || sc1 || sc2)`
+        // Consolidated seed is: `hash(domain || round || (v0, sc0) || (v1, sc1) || (v2, sc2))`
         // Consolidated randomness is None (VDF is not calculated yet)
-        let sc2 = rng.gen::<u64>().to_string();
-        let tx2 = {
-            let (pub_key, sec_key) = validators[2].service_keypair();
-            TxPublishSeedCommitment::new(pub_key, &sc2, sec_key)
-        };
-        testkit.create_block_with_transactions(txvec![tx2]);
+        for (i, &(ref value, ref salt, _)) in contributions.iter().enumerate() {
+            let tx = {
+                let (pub_key, sec_key) = validators[i].service_keypair();
+                TxRevealSeed::new(pub_key, round_num, &value.to_bytes(), salt, sec_key)
+            };
+            testkit.create_block_with_transactions(txvec![tx]);
+        }
 
-        // As soon as +2/3 of seed commitments is collected, nodes are proceeding
+        // As soon as +2/3 of seed contributions is revealed, nodes are proceeding
         // to calculate their combined seed value and VDF(seed) values
         // to create their unpredictable random value
 
-        // Combine and sort commitments
-        let mut commitments = [sc0.clone(), sc1.clone(), sc2.clone()].to_vec();
-        commitments.sort_unstable();
+        // Combine contributions with the keys of their authors
+        let revealed = [sc0.clone(), sc1.clone(), sc2.clone()]
+            .iter()
+            .enumerate()
+            .map(|(i, value)| (*validators[i].service_keypair().0, value.clone()))
+            .collect::<Vec<_>>();
 
-        let combined_seed = calculate_combined_seed(&commitments);
+        let combined_seed = calculate_combined_seed(round_num, &revealed);
 
         assert_storage_values_eq(
             testkit.snapshot(),
             &validators,
             Some(combined_seed),
             prev_randomness.clone(),
+            &[Some(c0), Some(c1), Some(c2), None],
             &[Some(sc0.clone()), Some(sc1.clone()), Some(sc2.clone()), None],
             &[None, None, None, None],
         );
@@ -183,15 +259,18 @@ fn test_exonum_rng_service_with_4_validators() {
         // Publish VDF result from validator 0
         // After that validators seed commitments look like this:
         // number       | 0       | 1       | 2       | 3    |
-        // commitment   | `sc0`   | `sc1`   | `sc2`   | None |
+        // commitment   | `c0`    | `c1`    | `c2`    | None |
+        // reveal       | `sc0`   | `sc1`   | `sc2`   | None |
         // vdf result   | `vdf0`  | None    | None    | None |
         //
-        // Consolidated seed is: `hash(sc0 || sc1 || sc2)`
+        // Consolidated seed is: `hash(domain || round || (v0, sc0) || (v1, sc1) || (v2, sc2))`
         // Consolidated randomness is None (not enough VDF results)
-        let vdf_res0 = calculate_vdf(&combined_seed).unwrap();
+        let vdf_res0 = BigInt::new(
+            calculate_vdf(&MimcVdf, &combined_seed, DEFAULT_VDF_DIFFICULTY).unwrap().value,
+        ).unwrap();
         let vdf_tx0 = {
             let (pub_key, sec_key) = validators[0].service_keypair();
-            TxPublishVdfResult::new(pub_key, &combined_seed, &vdf_res0, sec_key)
+            TxPublishVdfResult::new(pub_key, &combined_seed, &vdf_res0.to_bytes(), &[], sec_key)
         };
         testkit.create_block_with_transactions(txvec![vdf_tx0]);
 
@@ -200,6 +279,7 @@ fn test_exonum_rng_service_with_4_validators() {
             &validators,
             Some(combined_seed),
             prev_randomness.clone(),
+            &[Some(c0), Some(c1), Some(c2), None],
             &[Some(sc0.clone()), Some(sc1.clone()), Some(sc2.clone()), None],
             &[Some(vdf_res0.clone()), None, None, None],
         );
@@ -207,15 +287,18 @@ fn test_exonum_rng_service_with_4_validators() {
         // Publish VDF result from validator 1
         // After that validators seed commitments look like this:
         // number       | 0       | 1       | 2       | 3    |
-        // commitment   | `sc0`   | `sc1`   | `sc2`   | None |
+        // commitment   | `c0`    | `c1`    | `c2`    | None |
+        // reveal       | `sc0`   | `sc1`   | `sc2`   | None |
         // vdf result   | `vdf0`  | `vdf1`  | None    | None |
         //
-        // Consolidated seed is: `hash(sc0 || sc1 || sc2)`
+        // Consolidated seed is: `hash(domain || round || (v0, sc0) || (v1, sc1) || (v2, sc2))`
         // Consolidated randomness is None (not enough VDF results)
-        let vdf_res1 = calculate_vdf(&combined_seed).unwrap();
+        let vdf_res1 = BigInt::new(
+            calculate_vdf(&MimcVdf, &combined_seed, DEFAULT_VDF_DIFFICULTY).unwrap().value,
+        ).unwrap();
         let vdf_tx1 = {
             let (pub_key, sec_key) = validators[1].service_keypair();
-            TxPublishVdfResult::new(pub_key, &combined_seed, &vdf_res1, sec_key)
+            TxPublishVdfResult::new(pub_key, &combined_seed, &vdf_res1.to_bytes(), &[], sec_key)
         };
         testkit.create_block_with_transactions(txvec![vdf_tx1]);
 
@@ -224,6 +307,7 @@ fn test_exonum_rng_service_with_4_validators() {
             &validators,
             Some(combined_seed),
             prev_randomness.clone(),
+            &[Some(c0), Some(c1), Some(c2), None],
             &[Some(sc0.clone()), Some(sc1.clone()), Some(sc2.clone()), None],
             &[Some(vdf_res0.clone()), Some(vdf_res1.clone()), None, None],
         );
@@ -231,31 +315,744 @@ fn test_exonum_rng_service_with_4_validators() {
         // Publish VDF result from validator 2
         // After that validators seed commitments look like this:
         // number       | 0       | 1       | 2       | 3    |
-        // commitment   | `sc0`   | `sc1`   | `sc2`   | None |
+        // commitment   | `c0`    | `c1`    | `c2`    | None |
+        // reveal       | `sc0`   | `sc1`   | `sc2`   | None |
         // vdf result   | `vdf0`  | `vdf1`  | `vdf2`  | None |
         //
-        // Consolidated seed is: `hash(sc0 || sc1 || sc2)`
-        // Consolidated randomness is `vdf0`
+        // Consolidated seed is: `hash(domain || round || (v0, sc0) || (v1, sc1) || (v2, sc2))`
+        // Consolidated randomness is `hash(domain || round || seed || vdf0)`
         println!("vdf tx 2");
-        let vdf_res2 = calculate_vdf(&combined_seed).unwrap();
+        let vdf_res2 = BigInt::new(
+            calculate_vdf(&MimcVdf, &combined_seed, DEFAULT_VDF_DIFFICULTY).unwrap().value,
+        ).unwrap();
         let vdf_tx2 = {
             let (pub_key, sec_key) = validators[2].service_keypair();
-            TxPublishVdfResult::new(pub_key, &combined_seed, &vdf_res2, sec_key)
+            TxPublishVdfResult::new(pub_key, &combined_seed, &vdf_res2.to_bytes(), &[], sec_key)
         };
         testkit.create_block_with_transactions(txvec![vdf_tx2]);
 
-        // At this moment there's available randomness equal to VDF results
+        // At this moment there's available randomness derived from the agreed VDF result
         // And seed, seed commitments and vdf results are reset
+        assert_eq!(vdf_res0, vdf_res2);
+        let randomness = calculate_randomness(round_num, &combined_seed, &vdf_res0);
         assert_storage_values_eq(
             testkit.snapshot(),
             &validators,
             None,
-            Some(vdf_res0.to_hash()),
+            Some(randomness),
+            &[None, None, None, None],
             &[None, None, None, None],
             &[None, None, None, None],
         );
 
-        println!("Resulting randomness generated in round {}: {}", round_num, vdf_res0.to_hash().to_hex());
-        prev_randomness = Some(vdf_res0.to_hash());
+        // Finalized round is recorded into the history
+        {
+            let schema = RngSchema::new(testkit.snapshot());
+            assert_eq!(schema.current_round(), round_num + 1);
+            assert_eq!(schema.rounds().len(), round_num + 1);
+
+            let info = schema.round_info(round_num).unwrap();
+            assert_eq!(info.round(), round_num);
+            assert_eq!(*info.seed(), combined_seed);
+            assert_eq!(info.commitments().len(), 3);
+            assert_eq!(info.output(), Ok(vdf_res2.clone()));
+            assert_eq!(info.difficulty(), DEFAULT_VDF_DIFFICULTY);
+            assert_eq!(info.height(), testkit.height());
+            assert_eq!(*info.randomness(), randomness);
+            assert!(info.disagreeing_validators().is_empty());
+        }
+
+        println!("Resulting randomness generated in round {}: {}", round_num, randomness.to_hex());
+        prev_randomness = Some(randomness);
+    }
+}
+
+#[test]
+fn test_vdf_difficulty_configuration_change() {
+    let mut testkit = TestKitBuilder::validator()
+        .with_validators(4)
+        .with_service(ExonumRngService::with_config(RngConfig {
+            vdf_difficulty: 1000,
+            ..Default::default()
+        }))
+        .create();
+
+    assert_eq!(RngSchema::new(testkit.snapshot()).vdf_difficulty(), 1000);
+
+    let new_config = RngConfig {
+        vdf_difficulty: 2000,
+        ..Default::default()
+    };
+    let cfg_change_height = Height(5);
+    let proposal = {
+        let mut cfg = testkit.configuration_change_proposal();
+        cfg.set_service_config(SERVICE_NAME, new_config.clone());
+        cfg.set_actual_from(cfg_change_height);
+        cfg
+    };
+    testkit.commit_configuration_change(proposal);
+
+    testkit.create_blocks_until(cfg_change_height);
+    let schema = RngSchema::new(testkit.snapshot());
+    assert_eq!(schema.actual_config(), new_config);
+    assert_eq!(schema.vdf_difficulty(), 2000);
+}
+
+#[test]
+fn test_non_validator_submissions_are_rejected() {
+    let mut testkit = TestKitBuilder::validator()
+        .with_validators(4)
+        .with_service(ExonumRngService::new())
+        .create();
+
+    let (pub_key, sec_key) = crypto::gen_keypair();
+    let salt = Hash::zero();
+    let commitment = calculate_seed_commitment(&BigInt::from(42u64), &salt);
+
+    let commit_tx = TxPublishSeedCommitment::new(&pub_key, 0, &commitment, &sec_key);
+    testkit.create_block_with_transactions(txvec![commit_tx.clone()]);
+    assert_tx_error(testkit.snapshot(), &commit_tx.hash(), Error::NotAValidator);
+
+    let value = BigInt::from(42u64).to_bytes();
+    let vdf_tx = TxPublishVdfResult::new(&pub_key, &Hash::zero(), &value, &[], &sec_key);
+    testkit.create_block_with_transactions(txvec![vdf_tx.clone()]);
+    assert_tx_error(testkit.snapshot(), &vdf_tx.hash(), Error::NotAValidator);
+
+    let schema = RngSchema::new(testkit.snapshot());
+    assert_eq!(schema.num_seed_commitments(), 0);
+    assert_eq!(schema.num_vdf_results(), 0);
+}
+
+#[test]
+fn test_rejected_submissions() {
+    let mut testkit = TestKitBuilder::validator()
+        .with_validators(4)
+        .with_service(ExonumRngService::new())
+        .create();
+
+    let validators = testkit.network().validators().to_vec();
+    let salt = Hash::zero();
+    let values = (1..4u64).map(BigInt::from).collect::<Vec<_>>();
+    let answer = BigInt::from(42u64).to_bytes();
+
+    // VDF result can't be published before the seed is agreed on
+    let (pub_key, sec_key) = validators[0].service_keypair();
+    let tx = TxPublishVdfResult::new(pub_key, &Hash::zero(), &answer, &[], sec_key);
+    testkit.create_block_with_transactions(txvec![tx.clone()]);
+    assert_tx_error(testkit.snapshot(), &tx.hash(), Error::NoSeed);
+
+    // Commitment for another round
+    let commitment = calculate_seed_commitment(&values[0], &salt);
+    let tx = TxPublishSeedCommitment::new(pub_key, 1, &commitment, sec_key);
+    testkit.create_block_with_transactions(txvec![tx.clone()]);
+    assert_tx_error(testkit.snapshot(), &tx.hash(), Error::WrongRound);
+
+    // Second commitment from the same validator
+    let tx = TxPublishSeedCommitment::new(pub_key, 0, &commitment, sec_key);
+    let duplicate = TxPublishSeedCommitment::new(pub_key, 0, &salt, sec_key);
+    testkit.create_block_with_transactions(txvec![tx, duplicate.clone()]);
+    assert_tx_error(testkit.snapshot(), &duplicate.hash(), Error::DuplicateSubmission);
+
+    for i in 1..3 {
+        let (pub_key, sec_key) = validators[i].service_keypair();
+        let commitment = calculate_seed_commitment(&values[i], &salt);
+        let tx = TxPublishSeedCommitment::new(pub_key, 0, &commitment, sec_key);
+        testkit.create_block_with_transactions(txvec![tx]);
+    }
+
+    // Commit phase is over
+    let (pub_key, sec_key) = validators[3].service_keypair();
+    let tx = TxPublishSeedCommitment::new(pub_key, 0, &salt, sec_key);
+    testkit.create_block_with_transactions(txvec![tx.clone()]);
+    assert_tx_error(testkit.snapshot(), &tx.hash(), Error::CommitPhaseOver);
+
+    // Validator without commitment can't reveal
+    let tx = TxRevealSeed::new(pub_key, 0, &BigInt::from(4u64).to_bytes(), &salt, sec_key);
+    testkit.create_block_with_transactions(txvec![tx.clone()]);
+    assert_tx_error(testkit.snapshot(), &tx.hash(), Error::NotCommitted);
+
+    // Non-canonical contribution
+    let (pub_key, sec_key) = validators[0].service_keypair();
+    let tx = TxRevealSeed::new(pub_key, 0, &[0, 1], &salt, sec_key);
+    testkit.create_block_with_transactions(txvec![tx.clone()]);
+    assert_tx_error(testkit.snapshot(), &tx.hash(), Error::MalformedBigInt);
+
+    for i in 0..3 {
+        let (pub_key, sec_key) = validators[i].service_keypair();
+        let tx = TxRevealSeed::new(pub_key, 0, &values[i].to_bytes(), &salt, sec_key);
+        testkit.create_block_with_transactions(txvec![tx]);
+    }
+
+    let seed = RngSchema::new(testkit.snapshot()).last_seed().get().unwrap();
+
+    // VDF result for another seed
+    let tx = TxPublishVdfResult::new(pub_key, &Hash::zero(), &answer, &[], sec_key);
+    testkit.create_block_with_transactions(txvec![tx.clone()]);
+    assert_tx_error(testkit.snapshot(), &tx.hash(), Error::SeedMismatch);
+
+    // Malformed VDF result
+    let tx = TxPublishVdfResult::new(pub_key, &seed, &[0, 0x42], &[], sec_key);
+    testkit.create_block_with_transactions(txvec![tx.clone()]);
+    assert_tx_error(testkit.snapshot(), &tx.hash(), Error::MalformedBigInt);
+
+    // Invalid VDF result
+    let tx = TxPublishVdfResult::new(pub_key, &seed, &answer, &[], sec_key);
+    testkit.create_block_with_transactions(txvec![tx.clone()]);
+    assert_tx_error(testkit.snapshot(), &tx.hash(), Error::InvalidVdf);
+
+    // Rejected results are not counted
+    let value = calculate_vdf(&MimcVdf, &seed, DEFAULT_VDF_DIFFICULTY)
+        .unwrap()
+        .encoded_value();
+    let tx = TxPublishVdfResult::new(pub_key, &seed, &value, &[], sec_key);
+    testkit.create_block_with_transactions(txvec![tx]);
+    assert_eq!(RngSchema::new(testkit.snapshot()).num_vdf_results(), 1);
+}
+
+#[test]
+fn test_signed_contributions() {
+    let mut testkit = TestKitBuilder::validator()
+        .with_validators(4)
+        .with_service(ExonumRngService::with_config(RngConfig {
+            contribution_source: ContributionSource::Signature,
+            ..Default::default()
+        }))
+        .create();
+
+    let validators = testkit.network().validators().to_vec();
+
+    // Signed contributions are deterministic and bound to the validator and the round
+    let (pub_key, sec_key) = validators[0].service_keypair();
+    let value = signed_contribution(0, sec_key);
+    assert_eq!(value, signed_contribution(0, sec_key));
+    assert!(verify_signed_contribution(&value, 0, pub_key));
+    assert!(!verify_signed_contribution(&value, 1, pub_key));
+    assert!(!verify_signed_contribution(&value, 0, validators[1].service_keypair().0));
+
+    // Validator 0 commits to a random value instead of the signed one
+    let values = vec![
+        random_contribution(),
+        signed_contribution(0, validators[1].service_keypair().1),
+        signed_contribution(0, validators[2].service_keypair().1),
+    ];
+    for (i, value) in values.iter().enumerate() {
+        let (pub_key, sec_key) = validators[i].service_keypair();
+        let commitment = calculate_seed_commitment(value, &signed_salt(0, sec_key));
+        let tx = TxPublishSeedCommitment::new(pub_key, 0, &commitment, sec_key);
+        testkit.create_block_with_transactions(txvec![tx]);
+    }
+
+    let reveals = values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            let (pub_key, sec_key) = validators[i].service_keypair();
+            TxRevealSeed::new(pub_key, 0, &value.to_bytes(), &signed_salt(0, sec_key), sec_key)
+        })
+        .collect::<Vec<_>>();
+    testkit.create_block_with_transactions(txvec![
+        reveals[0].clone(),
+        reveals[1].clone(),
+        reveals[2].clone(),
+    ]);
+
+    assert_tx_error(testkit.snapshot(), &reveals[0].hash(), Error::InvalidContributionSignature);
+
+    let schema = RngSchema::new(testkit.snapshot());
+    assert_eq!(schema.num_seed_reveals(), 2);
+    assert_eq!(schema.last_seed().get(), None);
+}
+
+#[test]
+fn test_round_keeps_contribution_source_it_started_with() {
+    let mut testkit = TestKitBuilder::validator()
+        .with_validators(4)
+        .with_service(ExonumRngService::new())
+        .create();
+
+    let validators = testkit.network().validators().to_vec();
+    let values = (0..3).map(|_| random_contribution()).collect::<Vec<_>>();
+    let salts = validators[1..]
+        .iter()
+        .map(|validator| signed_salt(0, validator.service_keypair().1))
+        .collect::<Vec<_>>();
+    let commitments = validators[1..]
+        .iter()
+        .enumerate()
+        .map(|(i, validator)| {
+            let (pub_key, sec_key) = validator.service_keypair();
+            let commitment = calculate_seed_commitment(&values[i], &salts[i]);
+            TxPublishSeedCommitment::new(pub_key, 0, &commitment, sec_key)
+        })
+        .collect::<Vec<_>>();
+    testkit.create_block_with_transactions(txvec![
+        commitments[0].clone(),
+        commitments[1].clone(),
+        commitments[2].clone(),
+    ]);
+
+    // Contributions are already committed when signed ones become required
+    let cfg_change_height = Height(4);
+    let proposal = {
+        let mut cfg = testkit.configuration_change_proposal();
+        cfg.set_service_config(
+            SERVICE_NAME,
+            RngConfig {
+                contribution_source: ContributionSource::Signature,
+                ..Default::default()
+            },
+        );
+        cfg.set_actual_from(cfg_change_height);
+        cfg
+    };
+    testkit.commit_configuration_change(proposal);
+    testkit.create_blocks_until(cfg_change_height);
+
+    let schema = RngSchema::new(testkit.snapshot());
+    assert_eq!(schema.actual_config().contribution_source, ContributionSource::Signature);
+    assert_eq!(schema.contribution_source(), ContributionSource::Os);
+
+    let reveals = validators[1..]
+        .iter()
+        .enumerate()
+        .map(|(i, validator)| {
+            let (pub_key, sec_key) = validator.service_keypair();
+            TxRevealSeed::new(pub_key, 0, &values[i].to_bytes(), &salts[i], sec_key)
+        })
+        .collect::<Vec<_>>();
+    testkit.create_block_with_transactions(txvec![
+        reveals[0].clone(),
+        reveals[1].clone(),
+        reveals[2].clone(),
+    ]);
+
+    let schema = RngSchema::new(testkit.snapshot());
+    assert_eq!(schema.num_seed_reveals(), 3);
+    assert!(schema.last_seed().get().is_some());
+
+    // Round is recorded with the source it has started with, the next one uses the new source
+    let mut fork = testkit.blockchain_mut().fork();
+    let mut schema = RngSchema::new(&mut fork);
+    let info = schema.abort_round(Height(5));
+    assert_eq!(info.contribution_source(), ContributionSource::Os.id());
+    assert_eq!(schema.contribution_source(), ContributionSource::Signature);
+}
+
+#[test]
+fn test_round_timeouts() {
+    let mut testkit = TestKitBuilder::validator()
+        .with_validators(4)
+        .with_service(ExonumRngService::with_config(RngConfig {
+            commit_timeout: 10,
+            vdf_timeout: 10,
+            ..Default::default()
+        }))
+        .create();
+
+    let validators = testkit.network().validators().to_vec();
+    let keys = validators
+        .iter()
+        .map(|validator| *validator.service_keypair().0)
+        .collect::<Vec<_>>();
+    let salt = Hash::zero();
+    let values = (1..4u64).map(BigInt::from).collect::<Vec<_>>();
+
+    // Only two validators commit, so the commit phase can't be completed
+    for i in 0..2 {
+        let (pub_key, sec_key) = validators[i].service_keypair();
+        let commitment = calculate_seed_commitment(&values[i], &salt);
+        let tx = TxPublishSeedCommitment::new(pub_key, 0, &commitment, sec_key);
+        testkit.create_block_with_transactions(txvec![tx]);
+    }
+
+    assert_eq!(RngSchema::new(testkit.snapshot()).phase_deadline(), Height(10));
+    testkit.create_blocks_until(Height(9));
+    assert_eq!(RngSchema::new(testkit.snapshot()).current_round(), 0);
+
+    // Round is aborted as soon as the deadline is reached
+    testkit.create_block_with_transactions(txvec![]);
+    {
+        let schema = RngSchema::new(testkit.snapshot());
+        assert_eq!(schema.current_round(), 1);
+        assert_eq!(schema.num_seed_commitments(), 0);
+        assert_eq!(schema.last_randomness().get(), None);
+
+        let info = schema.round_info(0).unwrap();
+        assert!(info.failed());
+        assert_eq!(*info.seed(), Hash::zero());
+        assert_eq!(info.height(), Height(10));
+        assert_eq!(info.missing_validators(), keys[2..].to_vec());
+    }
+
+    // Commitments to the aborted round are not accepted anymore
+    let (pub_key, sec_key) = validators[2].service_keypair();
+    let commitment = calculate_seed_commitment(&values[2], &salt);
+    let tx = TxPublishSeedCommitment::new(pub_key, 0, &commitment, sec_key);
+    testkit.create_block_with_transactions(txvec![tx.clone()]);
+    assert_tx_error(testkit.snapshot(), &tx.hash(), Error::WrongRound);
+
+    // Next round agrees on the seed, but nobody publishes VDF results
+    for i in 0..3 {
+        let (pub_key, sec_key) = validators[i].service_keypair();
+        let commitment = calculate_seed_commitment(&values[i], &salt);
+        let tx = TxPublishSeedCommitment::new(pub_key, 1, &commitment, sec_key);
+        testkit.create_block_with_transactions(txvec![tx]);
+    }
+    for i in 0..3 {
+        let (pub_key, sec_key) = validators[i].service_keypair();
+        let tx = TxRevealSeed::new(pub_key, 1, &values[i].to_bytes(), &salt, sec_key);
+        testkit.create_block_with_transactions(txvec![tx]);
+    }
+
+    let (seed, deadline) = {
+        let schema = RngSchema::new(testkit.snapshot());
+        let seed = schema.last_seed().get().unwrap();
+        assert_eq!(schema.seed_height().get(), Some(testkit.height().0));
+        (seed, schema.phase_deadline())
+    };
+    assert_eq!(deadline, Height(testkit.height().0 + 10));
+    while testkit.height() < deadline {
+        testkit.create_block_with_transactions(txvec![]);
+    }
+
+    let schema = RngSchema::new(testkit.snapshot());
+    assert_eq!(schema.current_round(), 2);
+    assert_eq!(schema.last_seed().get(), None);
+    assert_eq!(schema.round_start().get(), Some(deadline.0));
+
+    let info = schema.round_info(1).unwrap();
+    assert!(info.failed());
+    assert_eq!(*info.seed(), seed);
+    assert_eq!(info.commitments().len(), 3);
+    assert!(info.vdf_output().is_empty());
+    assert_eq!(info.missing_validators(), keys);
+}
+
+#[test]
+fn test_round_keeps_timeouts_it_started_with() {
+    let config = RngConfig {
+        commit_timeout: 10,
+        vdf_timeout: 10,
+        ..Default::default()
+    };
+    let mut testkit = TestKitBuilder::validator()
+        .with_validators(4)
+        .with_service(ExonumRngService::with_config(config.clone()))
+        .create();
+
+    let new_config = RngConfig {
+        commit_timeout: 20,
+        vdf_timeout: 20,
+        ..config
+    };
+    let cfg_change_height = Height(5);
+    let proposal = {
+        let mut cfg = testkit.configuration_change_proposal();
+        cfg.set_service_config(SERVICE_NAME, new_config.clone());
+        cfg.set_actual_from(cfg_change_height);
+        cfg
+    };
+    testkit.commit_configuration_change(proposal);
+    testkit.create_blocks_until(cfg_change_height);
+
+    {
+        let schema = RngSchema::new(testkit.snapshot());
+        assert_eq!(schema.actual_config(), new_config);
+        assert_eq!(schema.commit_timeout(), 10);
+        assert_eq!(schema.vdf_timeout(), 10);
+        assert_eq!(schema.phase_deadline(), Height(10));
+    }
+
+    // Open round is aborted at its original deadline
+    testkit.create_blocks_until(Height(10));
+    let schema = RngSchema::new(testkit.snapshot());
+    assert_eq!(schema.current_round(), 1);
+    assert!(schema.round_info(0).unwrap().failed());
+
+    // Next round starts with the new timeouts
+    assert_eq!(schema.commit_timeout(), 20);
+    assert_eq!(schema.vdf_timeout(), 20);
+    assert_eq!(schema.phase_deadline(), Height(30));
+}
+
+#[test]
+fn test_malformed_configuration_is_rejected() {
+    let config = RngConfig {
+        vdf_difficulty: 1000,
+        ..Default::default()
+    };
+    let mut testkit = TestKitBuilder::validator()
+        .with_validators(4)
+        .with_service(ExonumRngService::with_config(config.clone()))
+        .create();
+
+    let malformed = vec![
+        json!({ "vdf_difficulty": "fast" }),
+        json!({ "vdf_difficulty": 1000, "vdf_backend": "unknown" }),
+        json!({ "vdf_difficulty": 0 }),
+    ];
+    for value in malformed {
+        let cfg_change_height = testkit.height().next().next();
+        let proposal = {
+            let mut cfg = testkit.configuration_change_proposal();
+            cfg.set_service_config(SERVICE_NAME, value);
+            cfg.set_actual_from(cfg_change_height);
+            cfg
+        };
+        testkit.commit_configuration_change(proposal);
+        testkit.create_blocks_until(cfg_change_height);
+
+        let snapshot = testkit.snapshot();
+        assert!(RngConfig::actual(&snapshot).is_err());
+        // Service keeps the configuration it has been running with
+        assert_eq!(RngSchema::new(&snapshot).actual_config(), config);
+    }
+
+    let new_config = RngConfig {
+        vdf_difficulty: 2000,
+        ..config
+    };
+    let cfg_change_height = testkit.height().next().next();
+    let proposal = {
+        let mut cfg = testkit.configuration_change_proposal();
+        cfg.set_service_config(SERVICE_NAME, new_config.clone());
+        cfg.set_actual_from(cfg_change_height);
+        cfg
+    };
+    testkit.commit_configuration_change(proposal);
+    testkit.create_blocks_until(cfg_change_height);
+    assert_eq!(RngSchema::new(testkit.snapshot()).actual_config(), new_config);
+}
+
+#[test]
+fn test_no_contributions_while_syncing() {
+    // Any lag behind the local clock means that the node is syncing
+    let mut syncing = TestKitBuilder::validator()
+        .with_validators(4)
+        .with_service(ExonumRngService::new().with_sync_lag(Duration::from_secs(0)))
+        .create();
+    let mut following = TestKitBuilder::validator()
+        .with_validators(4)
+        .with_service(ExonumRngService::new())
+        .create();
+
+    for testkit in &mut [&mut syncing, &mut following] {
+        testkit.create_block();
+        testkit.create_block();
+    }
+
+    let us = *following.network().us().service_keypair().0;
+    assert!(RngSchema::new(following.snapshot())
+        .validators_seed_commitments()
+        .contains(&us));
+    assert_eq!(RngSchema::new(syncing.snapshot()).num_seed_commitments(), 0);
+}
+
+#[test]
+fn test_contributions_are_resumed_after_syncing() {
+    let dir = TempDir::new("exonum_rng").unwrap();
+    let path = dir.path().join("contribution.json");
+    let config = RngConfig {
+        vdf_difficulty: 100,
+        ..Default::default()
+    };
+    let service = |sync_lag| -> Box<Service> {
+        Box::new(
+            ExonumRngService::with_config(config.clone())
+                .with_contribution_file(ContributionFile::new(&path))
+                .with_sync_lag(sync_lag),
+        )
+    };
+    let syncing = Duration::from_secs(0);
+    let synced = Duration::from_secs(3600);
+
+    let mut testkit = TestKitBuilder::validator()
+        .with_validators(4)
+        .with_service(service(synced))
+        .create();
+    testkit.create_block();
+    testkit.create_block();
+
+    let us = *testkit.network().us().service_keypair().0;
+    let validators = testkit.network().validators().to_vec();
+    for i in 1..3 {
+        let (pub_key, sec_key) = validators[i].service_keypair();
+        let commitment = calculate_seed_commitment(&BigInt::from(i as u64), &Hash::zero());
+        let tx = TxPublishSeedCommitment::new(pub_key, 0, &commitment, sec_key);
+        testkit.create_block_with_transactions(txvec![tx]);
     }
-}
\ No newline at end of file
+    assert!(RngSchema::new(testkit.snapshot()).is_commit_phase_over());
+
+    // Committed contribution isn't revealed while the node is syncing...
+    let mut testkit = testkit.stop().resume(vec![service(syncing)]);
+    testkit.create_block();
+    testkit.create_block();
+    assert!(!RngSchema::new(testkit.snapshot())
+        .validators_seed_reveals()
+        .contains(&us));
+
+    // ...but is revealed once the node reaches the tip of the chain
+    let mut testkit = testkit.stop().resume(vec![service(synced)]);
+    testkit.create_block();
+    testkit.create_block();
+    assert!(RngSchema::new(testkit.snapshot())
+        .validators_seed_reveals()
+        .contains(&us));
+
+    // VDF isn't evaluated while the node is syncing...
+    let mut testkit = testkit.stop().resume(vec![service(syncing)]);
+    let reveals = (1..3)
+        .map(|i| {
+            let (pub_key, sec_key) = validators[i].service_keypair();
+            TxRevealSeed::new(pub_key, 0, &BigInt::from(i as u64).to_bytes(), &Hash::zero(), sec_key)
+        })
+        .collect::<Vec<_>>();
+    testkit.create_block_with_transactions(txvec![reveals[0].clone(), reveals[1].clone()]);
+    assert!(RngSchema::new(testkit.snapshot()).last_seed().exists());
+    for _ in 0..5 {
+        thread::sleep(Duration::from_millis(100));
+        testkit.create_block();
+    }
+    assert!(!RngSchema::new(testkit.snapshot())
+        .validators_vdf_results()
+        .contains(&us));
+
+    // ...and the result is published once the node reaches the tip of the chain
+    let mut testkit = testkit.stop().resume(vec![service(synced)]);
+    for _ in 0..100 {
+        testkit.create_block();
+        if RngSchema::new(testkit.snapshot()).validators_vdf_results().contains(&us) {
+            return;
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+    panic!("VDF result isn't published after the node has caught up");
+}
+
+#[test]
+fn test_validator_set_change_restarts_round() {
+    let mut testkit = TestKitBuilder::validator()
+        .with_validators(4)
+        .with_service(ExonumRngService::new())
+        .create();
+
+    let validators = testkit.network().validators().to_vec();
+    let keys = validators
+        .iter()
+        .map(|validator| *validator.service_keypair().0)
+        .collect::<Vec<_>>();
+    let salt = Hash::zero();
+
+    for i in 1..3 {
+        let (pub_key, sec_key) = validators[i].service_keypair();
+        let commitment = calculate_seed_commitment(&BigInt::from(i as u64), &salt);
+        let tx = TxPublishSeedCommitment::new(pub_key, 0, &commitment, sec_key);
+        testkit.create_block_with_transactions(txvec![tx]);
+    }
+
+    let cfg_change_height = Height(5);
+    let proposal = {
+        let mut cfg = testkit.configuration_change_proposal();
+        cfg.set_validators(validators[..3].to_vec());
+        cfg.set_actual_from(cfg_change_height);
+        cfg
+    };
+    testkit.commit_configuration_change(proposal);
+
+    // Round keeps its validator set and quorum until the change takes effect
+    while testkit.height() < Height(cfg_change_height.0 - 1) {
+        testkit.create_block_with_transactions(txvec![]);
+    }
+    {
+        let schema = RngSchema::new(testkit.snapshot());
+        assert_eq!(schema.current_round(), 0);
+        assert_eq!(schema.validators(), keys);
+        assert_eq!(schema.quorum(), 3);
+        assert!(!schema.is_commit_phase_over());
+    }
+
+    // Open round is aborted and the next one starts with the new validator set
+    while testkit.height() <= cfg_change_height {
+        testkit.create_block_with_transactions(txvec![]);
+    }
+    {
+        let schema = RngSchema::new(testkit.snapshot());
+        assert_eq!(schema.current_round(), 1);
+        assert_eq!(schema.validators(), keys[..3].to_vec());
+
+        // History keeps the validator set the aborted round has been made with
+        let info = schema.round_info(0).unwrap();
+        assert!(info.failed());
+        assert_eq!(info.validators(), keys);
+        assert_eq!(schema.quorum(), 1);
+        assert!(!schema.is_validator_set_changed());
+
+        // Validators haven't missed the round, it's been aborted by the configuration change
+        assert_eq!(info.missing_validators(), vec![keys[0], keys[3]]);
+        for pub_key in &info.missing_validators() {
+            let penalties = schema
+                .validator_stats()
+                .get(pub_key)
+                .map_or((0, 0), |stats| (stats.rounds_missed(), stats.rounds_locked_out()));
+            assert_eq!(penalties, (0, 0));
+        }
+    }
+
+    // Departed validator can't participate anymore
+    let (pub_key, sec_key) = validators[3].service_keypair();
+    let commitment = calculate_seed_commitment(&BigInt::from(3u64), &salt);
+    let tx = TxPublishSeedCommitment::new(pub_key, 1, &commitment, sec_key);
+    testkit.create_block_with_transactions(txvec![tx.clone()]);
+    assert_tx_error(testkit.snapshot(), &tx.hash(), Error::NotAValidator);
+
+    let (pub_key, sec_key) = validators[1].service_keypair();
+    let commitment = calculate_seed_commitment(&BigInt::from(1u64), &salt);
+    let tx = TxPublishSeedCommitment::new(pub_key, 1, &commitment, sec_key);
+    testkit.create_block_with_transactions(txvec![tx]);
+    assert!(RngSchema::new(testkit.snapshot()).is_commit_phase_over());
+}
+
+#[test]
+fn test_contribution_is_revealed_after_restart() {
+    let dir = TempDir::new("exonum_rng").unwrap();
+    let path = dir.path().join("contribution.json");
+    let mut testkit = TestKitBuilder::validator()
+        .with_validators(4)
+        .with_service(ExonumRngService::new().with_contribution_file(ContributionFile::new(&path)))
+        .create();
+
+    // Our commitment is published from the contribution saved to the file
+    testkit.create_block();
+    testkit.create_block();
+    let us = *testkit.network().us().service_keypair().0;
+    let (value, salt) = ContributionFile::new(&path).load(0).unwrap();
+    assert_eq!(
+        RngSchema::new(testkit.snapshot()).validators_seed_commitments().get(&us),
+        Some(calculate_seed_commitment(&value, &salt))
+    );
+
+    // Commit phase is closed by the others, so the contribution must be revealed
+    let validators = testkit.network().validators().to_vec();
+    for i in 1..3 {
+        let (pub_key, sec_key) = validators[i].service_keypair();
+        let commitment = calculate_seed_commitment(&BigInt::from(i as u64), &Hash::zero());
+        let tx = TxPublishSeedCommitment::new(pub_key, 0, &commitment, sec_key);
+        testkit.create_block_with_transactions(txvec![tx]);
+    }
+    assert!(RngSchema::new(testkit.snapshot()).is_commit_phase_over());
+
+    // Node is restarted before its reveal gets into a block
+    let stopped = testkit.stop();
+    let mut testkit = stopped.resume(vec![Box::new(
+        ExonumRngService::new().with_contribution_file(ContributionFile::new(&path)),
+    )]);
+    testkit.create_block();
+    testkit.create_block();
+
+    let schema = RngSchema::new(testkit.snapshot());
+    assert_eq!(
+        schema.validators_seed_reveals().get(&us).map(|reveal| reveal.contribution()),
+        Some(value)
+    );
+    assert!(schema.equivocations().is_empty());
+}